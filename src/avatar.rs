@@ -0,0 +1,91 @@
+/// Widest avatar that fits on an employee card, in characters
+static MAX_WIDTH: usize = 62;
+
+/// Rows of every letter
+static HEIGHT: usize = 6;
+
+/// Block letter rendering of `name`, the same style as the hand drawn avatars. Names too long for
+/// the card are written with their initials, and cut short when even those don't fit.
+pub fn generate(name: &str) -> String {
+    let full = name
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let text = if width(&full) <= MAX_WIDTH {
+        full
+    } else {
+        name.split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .collect()
+    };
+
+    let mut rows = vec![String::new(); HEIGHT];
+    let mut used = 0;
+    for c in text.chars() {
+        let glyph = glyph(c);
+        let glyph_width = glyph[0].len() * 2 + 2;
+        if used + glyph_width > MAX_WIDTH {
+            break;
+        }
+        used += glyph_width;
+
+        for (row, pixels) in rows.iter_mut().zip(glyph) {
+            for pixel in pixels.chars() {
+                row.push_str(if pixel == '#' { "██" } else { "  " });
+            }
+            row.push_str("  ");
+        }
+    }
+
+    rows.iter()
+        .map(|row| row.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn width(text: &str) -> usize {
+    text.chars().map(|c| glyph(c)[0].len() * 2 + 2).sum()
+}
+
+/// Pixels of a letter, letters with accents are drawn without them and anything that isn't a
+/// letter as a question mark
+fn glyph(c: char) -> [&'static str; 6] {
+    let c = match c.to_uppercase().next().unwrap_or(c) {
+        'Ä' | 'Å' | 'Á' | 'À' | 'Â' => 'A',
+        'Ö' | 'Ó' | 'Ò' | 'Ô' | 'Ø' => 'O',
+        'Ü' | 'Ú' | 'Ù' | 'Û' => 'U',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        c => c,
+    };
+
+    match c {
+        'A' => [" ## ", "#  #", "#  #", "####", "#  #", "#  #"],
+        'B' => ["### ", "#  #", "### ", "#  #", "#  #", "### "],
+        'C' => [" ###", "#   ", "#   ", "#   ", "#   ", " ###"],
+        'D' => ["### ", "#  #", "#  #", "#  #", "#  #", "### "],
+        'E' => ["####", "#   ", "### ", "#   ", "#   ", "####"],
+        'F' => ["####", "#   ", "### ", "#   ", "#   ", "#   "],
+        'G' => [" ###", "#   ", "# ##", "#  #", "#  #", " ###"],
+        'H' => ["#  #", "#  #", "####", "#  #", "#  #", "#  #"],
+        'I' => ["###", " # ", " # ", " # ", " # ", "###"],
+        'J' => ["  ##", "   #", "   #", "   #", "#  #", " ## "],
+        'K' => ["#  #", "# # ", "##  ", "# # ", "#  #", "#  #"],
+        'L' => ["#   ", "#   ", "#   ", "#   ", "#   ", "####"],
+        'M' => ["#   #", "## ##", "# # #", "#   #", "#   #", "#   #"],
+        'N' => ["#   #", "##  #", "# # #", "#  ##", "#   #", "#   #"],
+        'O' => ["####", "#  #", "#  #", "#  #", "#  #", "####"],
+        'P' => ["### ", "#  #", "#  #", "### ", "#   ", "#   "],
+        'Q' => [" ## ", "#  #", "#  #", "#  #", "# ##", " ###"],
+        'R' => ["### ", "#  #", "#  #", "### ", "# # ", "#  #"],
+        'S' => [" ###", "#   ", " ## ", "   #", "   #", "### "],
+        'T' => ["#####", "  #  ", "  #  ", "  #  ", "  #  ", "  #  "],
+        'U' => ["#  #", "#  #", "#  #", "#  #", "#  #", "####"],
+        'V' => ["#   #", "#   #", "#   #", " # # ", " # # ", "  #  "],
+        'W' => ["#   #", "#   #", "#   #", "# # #", "## ##", "#   #"],
+        'X' => ["#   #", " # # ", "  #  ", "  #  ", " # # ", "#   #"],
+        'Y' => ["#   #", " # # ", "  #  ", "  #  ", "  #  ", "  #  "],
+        'Z' => ["####", "   #", "  # ", " #  ", "#   ", "####"],
+        _ => [" ## ", "#  #", "  # ", " #  ", "    ", " #  "],
+    }
+}
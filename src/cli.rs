@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::{
+    clock::{Pace, TICK_RATE},
+    kanban::RunLength,
+};
+
+/// Simulates a software team crunching through a kanban board.
+#[derive(Debug, Parser)]
+#[command(name = "crunch", version)]
+pub struct Cli {
+    /// What to do, `run` when left out
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run a simulation, in the terminal UI or headless
+    Run(RunArgs),
+    /// Run a recorded simulation again and check that it ends the same way
+    Replay(ReplayArgs),
+    /// Check a scenario file and list every problem found in it
+    ValidateScenario {
+        /// Scenario file to check
+        path: PathBuf,
+    },
+    /// Run a scenario headless once per seed and collect the summaries as JSON lines
+    Batch(BatchArgs),
+}
+
+#[derive(Debug, Default, Args)]
+pub struct RunArgs {
+    /// Scenario file to run, the built-in scenario when left out
+    #[arg(long, value_name = "PATH")]
+    pub scenario: Option<PathBuf>,
+    /// Seed of the run, overrides the one of the scenario. A fresh one is picked when neither is
+    /// given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Run without the terminal UI. Runs one simulated day unless told otherwise
+    #[arg(long, conflicts_with = "step")]
+    pub headless: bool,
+    #[command(flatten)]
+    pub length: LengthArgs,
+    #[command(flatten)]
+    pub pace: PaceArgs,
+    /// Write the end of run summary to this file. Headless runs print it to stdout otherwise
+    #[arg(long, value_name = "PATH")]
+    pub summary: Option<PathBuf>,
+    /// Write a record of the run to this file, to be used with `crunch replay`
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+    /// Write the skills of every employee at the start of every day to this file, as CSV
+    #[arg(long, value_name = "PATH")]
+    pub history: Option<PathBuf>,
+    /// Write every change to the simulation to this file as it happens, as JSON lines
+    #[arg(long, value_name = "PATH")]
+    pub events: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// Record written by `crunch run --record`
+    pub record: PathBuf,
+    /// Watch the replay in the terminal UI instead of running it headless
+    #[arg(long)]
+    pub watch: bool,
+    #[command(flatten)]
+    pub pace: PaceArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Scenario file to run, the built-in scenario when left out
+    #[arg(long, value_name = "PATH")]
+    pub scenario: Option<PathBuf>,
+    /// Seed of the first run, every following run uses the next seed, wrapping around to 0
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// How many runs to do
+    #[arg(long, default_value_t = 10)]
+    pub runs: u64,
+    #[command(flatten)]
+    pub length: LengthArgs,
+    /// Write the summaries to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct LengthArgs {
+    /// Stop after this many ticks
+    #[arg(long, conflicts_with = "days", value_parser = clap::value_parser!(u64).range(1..))]
+    pub ticks: Option<u64>,
+    /// Stop after this many simulated days
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub days: Option<u64>,
+}
+
+impl LengthArgs {
+    pub fn run_length(&self) -> Option<RunLength> {
+        match (self.ticks, self.days) {
+            (Some(ticks), _) => Some(RunLength::Ticks(ticks)),
+            (None, Some(days)) => Some(RunLength::Days(days)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Args)]
+#[group(multiple = false)]
+pub struct PaceArgs {
+    /// Tick as fast as possible, the default when headless
+    #[arg(long)]
+    pub fast: bool,
+    /// Tick at this multiple of the normal rate of 10 ticks per second
+    #[arg(long, value_parser = parse_positive)]
+    pub speed: Option<f32>,
+    /// Ticks per real second
+    #[arg(long, value_name = "TICKS", value_parser = parse_positive)]
+    pub tick_rate: Option<f32>,
+    /// Start paused, ticking once per press of space
+    #[arg(long)]
+    pub step: bool,
+}
+
+impl PaceArgs {
+    pub fn pace(&self, headless: bool) -> Pace {
+        if self.fast {
+            Pace::Fast
+        } else if let Some(speed) = self.speed {
+            Pace::Speed(speed)
+        } else if let Some(tick_rate) = self.tick_rate {
+            Pace::Speed(tick_rate / TICK_RATE)
+        } else if self.step {
+            Pace::Step
+        } else if headless {
+            Pace::Fast
+        } else {
+            Pace::Speed(1.)
+        }
+    }
+}
+
+fn parse_positive(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(number) if number > 0. && number.is_finite() => Ok(number),
+        _ => Err(format!("expected a positive number, got {}", value)),
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How many ticks per real second are simulated at speed 1
+pub static TICK_RATE: f32 = 10.;
+
+/// Default amount of simulated minutes that pass with every tick
+pub const DEFAULT_MINUTES_PER_TICK: u64 = 10;
+
+pub const MINUTES_PER_DAY: u64 = 24 * 60;
+
+/// Simulated time, counted in ticks. Nothing in the simulation should look at the wall clock, only
+/// at this.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimClock {
+    ticks: u64,
+    minutes_per_tick: u64,
+}
+
+impl SimClock {
+    pub fn new(minutes_per_tick: u64) -> Self {
+        SimClock {
+            ticks: 0,
+            minutes_per_tick,
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.ticks += 1;
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn minutes_per_tick(&self) -> u64 {
+        self.minutes_per_tick
+    }
+
+    pub fn ticks_per_day(&self) -> u64 {
+        MINUTES_PER_DAY / self.minutes_per_tick
+    }
+
+    /// Simulated minutes since the start of the run
+    pub fn minutes(&self) -> u64 {
+        self.ticks * self.minutes_per_tick
+    }
+
+    /// Simulated days since the start of the run
+    pub fn days(&self) -> u64 {
+        self.minutes() / MINUTES_PER_DAY
+    }
+
+    pub fn hour_of_day(&self) -> u64 {
+        self.minutes() % MINUTES_PER_DAY / 60
+    }
+
+    pub fn minute_of_hour(&self) -> u64 {
+        self.minutes() % 60
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        SimClock::new(DEFAULT_MINUTES_PER_TICK)
+    }
+}
+
+impl Display for SimClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {}, {:02}:{:02}",
+            self.days(),
+            self.hour_of_day(),
+            self.minute_of_hour()
+        )
+    }
+}
+
+/// Part of the day the team is in
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DayPhase {
+    Working,
+    /// After hours, work still gets done but costs more energy
+    Overtime,
+    /// Everyone is resting
+    Off,
+}
+
+impl Display for DayPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DayPhase::Working => "working hours",
+            DayPhase::Overtime => "overtime",
+            DayPhase::Off => "off work",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Daily schedule of the team, in hours of the day
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct WorkingHours {
+    pub start_hour: u64,
+    pub end_hour: u64,
+    /// Hours the team keeps working after `end_hour`
+    pub overtime_hours: u64,
+    /// Days worked every week, the rest of the week is the weekend
+    pub work_days: u64,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        WorkingHours {
+            start_hour: 9,
+            end_hour: 17,
+            overtime_hours: 0,
+            work_days: 5,
+        }
+    }
+}
+
+impl WorkingHours {
+    pub fn phase(&self, clock: &SimClock) -> DayPhase {
+        let hour = clock.hour_of_day();
+        if clock.days() % 7 >= self.work_days {
+            DayPhase::Off
+        } else if (self.start_hour..self.end_hour).contains(&hour) {
+            DayPhase::Working
+        } else if (self.end_hour..self.end_hour + self.overtime_hours).contains(&hour) {
+            DayPhase::Overtime
+        } else {
+            DayPhase::Off
+        }
+    }
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        if self.start_hour >= self.end_hour {
+            problems.push(format!(
+                "working_hours: start_hour has to come before end_hour, got {} and {}",
+                self.start_hour, self.end_hour
+            ));
+        }
+        if self.end_hour + self.overtime_hours > 24 {
+            problems.push(
+                "working_hours: end_hour and overtime_hours have to end the day by midnight"
+                    .to_string(),
+            );
+        }
+        if !(1..=7).contains(&self.work_days) {
+            problems.push(format!(
+                "working_hours: work_days has to be between 1 and 7, got {}",
+                self.work_days
+            ));
+        }
+    }
+}
+
+/// How fast simulated time goes by compared to real time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Pace {
+    /// Next tick as soon as the previous one is done
+    Fast,
+    /// Multiplier on top of `TICK_RATE`
+    Speed(f32),
+    /// Only tick when asked to
+    Step,
+}
+
+impl Pace {
+    /// Real time to wait between two ticks, `None` when ticks are not scheduled automatically.
+    pub fn tick_interval(&self) -> Option<Duration> {
+        match *self {
+            Pace::Fast => Some(Duration::ZERO),
+            Pace::Speed(multiplier) => Some(Duration::from_secs_f32(1. / (TICK_RATE * multiplier))),
+            Pace::Step => None,
+        }
+    }
+
+    pub fn faster(&self) -> Pace {
+        match *self {
+            Pace::Speed(multiplier) => Pace::Speed((multiplier * 2.).min(64.)),
+            pace => pace,
+        }
+    }
+
+    pub fn slower(&self) -> Pace {
+        match *self {
+            Pace::Speed(multiplier) => Pace::Speed((multiplier / 2.).max(0.125)),
+            pace => pace,
+        }
+    }
+}
+
+impl Display for Pace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Pace::Fast => write!(f, "max speed"),
+            Pace::Speed(multiplier) => write!(f, "x{}", multiplier),
+            Pace::Step => write!(f, "paused, space to step"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Clock on `hour` of `day`, an hour per tick
+    fn at(day: u64, hour: u64) -> SimClock {
+        let mut clock = SimClock::new(60);
+        for _ in 0..day * 24 + hour {
+            clock.advance();
+        }
+        clock
+    }
+
+    #[test]
+    fn working_hours_include_the_start_hour_but_not_the_end_hour() {
+        let hours = WorkingHours::default();
+
+        assert_eq!(hours.phase(&at(0, 8)), DayPhase::Off);
+        assert_eq!(hours.phase(&at(0, 9)), DayPhase::Working);
+        assert_eq!(hours.phase(&at(0, 16)), DayPhase::Working);
+        assert_eq!(hours.phase(&at(0, 17)), DayPhase::Off);
+    }
+
+    #[test]
+    fn overtime_follows_working_hours() {
+        let hours = WorkingHours {
+            overtime_hours: 2,
+            ..WorkingHours::default()
+        };
+
+        assert_eq!(hours.phase(&at(0, 16)), DayPhase::Working);
+        assert_eq!(hours.phase(&at(0, 17)), DayPhase::Overtime);
+        assert_eq!(hours.phase(&at(0, 18)), DayPhase::Overtime);
+        assert_eq!(hours.phase(&at(0, 19)), DayPhase::Off);
+    }
+
+    #[test]
+    fn weekends_are_off_whatever_the_hour() {
+        let hours = WorkingHours {
+            overtime_hours: 2,
+            ..WorkingHours::default()
+        };
+
+        assert_eq!(hours.phase(&at(4, 10)), DayPhase::Working);
+        assert_eq!(hours.phase(&at(5, 10)), DayPhase::Off);
+        assert_eq!(hours.phase(&at(6, 17)), DayPhase::Off);
+        assert_eq!(hours.phase(&at(7, 10)), DayPhase::Working);
+    }
+
+    #[test]
+    fn a_seven_day_week_has_no_weekend() {
+        let hours = WorkingHours {
+            work_days: 7,
+            ..WorkingHours::default()
+        };
+
+        assert_eq!(hours.phase(&at(5, 10)), DayPhase::Working);
+        assert_eq!(hours.phase(&at(6, 10)), DayPhase::Working);
+    }
+}
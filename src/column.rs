@@ -0,0 +1,84 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::task::{Task, TaskId};
+
+/// Columns of the board, work items move through them from left to right
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+pub enum Column {
+    Backlog,
+    InProgress,
+    Review,
+    Merge,
+    Done,
+}
+
+impl Column {
+    pub const ALL: [Column; 5] = [
+        Column::Backlog,
+        Column::InProgress,
+        Column::Review,
+        Column::Merge,
+        Column::Done,
+    ];
+
+    /// Column a task on the board is in, `None` for tasks that are not part of a work item.
+    /// Delivered work items are the only ones in `Done`.
+    pub fn of(task: &Task) -> Option<Column> {
+        let column = Column::of_stage(task.id)?;
+        if task.stage == 0 && task.energy_taken == 0. && !task.rework {
+            Some(Column::Backlog)
+        } else {
+            Some(column)
+        }
+    }
+
+    /// Column a stage is worked on in
+    pub fn of_stage(id: TaskId) -> Option<Column> {
+        match id {
+            TaskId::Design | TaskId::CreatePR => Some(Column::InProgress),
+            TaskId::ReviewPR | TaskId::Test => Some(Column::Review),
+            TaskId::MergePR | TaskId::Deploy => Some(Column::Merge),
+            TaskId::CoffeeBreak => None,
+        }
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Column::Backlog => "Backlog",
+            Column::InProgress => "In Progress",
+            Column::Review => "Review",
+            Column::Merge => "Merge",
+            Column::Done => "Done",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Most work items a column can hold at once, columns without a limit take any amount.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct WipLimits(BTreeMap<Column, usize>);
+
+impl WipLimits {
+    pub fn limit(&self, column: Column) -> Option<usize> {
+        self.0.get(&column).copied()
+    }
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for (column, limit) in self.0.iter() {
+            if *column == Column::Done {
+                problems.push("wip_limits.Done: delivered work items can't be limited".to_string());
+            } else if *limit == 0 {
+                problems.push(format!("wip_limits.{:?} has to be at least 1", column));
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::workflow::WorkItemKind;
+
+/// Days before its due date a work item starts to go up in priority
+pub static ESCALATION_DAYS: u64 = 3;
+
+/// Days work items of every kind have to be delivered in once they show up on the board, kinds
+/// without any have no due date.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct DueDays(BTreeMap<WorkItemKind, u64>);
+
+impl DueDays {
+    pub fn days(&self, kind: WorkItemKind) -> Option<u64> {
+        self.0.get(&kind).copied()
+    }
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for (kind, days) in self.0.iter() {
+            if *days == 0 {
+                problems.push(format!("due_days.{:?} has to be at least 1", kind));
+            }
+        }
+    }
+}
+
+/// Priority a work item due on tick `due_at` has gained by tick `now`, one for every day it got
+/// closer than `ESCALATION_DAYS` to its due date, and one more for every day it is overdue.
+pub fn escalation(due_at: u64, now: u64, ticks_per_day: u64) -> u32 {
+    let escalates_at = due_at.saturating_sub(ESCALATION_DAYS * ticks_per_day);
+    if now < escalates_at {
+        0
+    } else {
+        ((now - escalates_at) / ticks_per_day + 1) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TICKS_PER_DAY: u64 = 10;
+
+    /// Due on the tick day 10 starts on, so escalation starts on day 7
+    static DUE_AT: u64 = 10 * TICKS_PER_DAY;
+
+    #[test]
+    fn no_escalation_before_the_last_days() {
+        assert_eq!(escalation(DUE_AT, 0, TICKS_PER_DAY), 0);
+        assert_eq!(escalation(DUE_AT, 7 * TICKS_PER_DAY - 1, TICKS_PER_DAY), 0);
+    }
+
+    #[test]
+    fn first_escalation_day_gives_one() {
+        assert_eq!(escalation(DUE_AT, 7 * TICKS_PER_DAY, TICKS_PER_DAY), 1);
+        assert_eq!(escalation(DUE_AT, 8 * TICKS_PER_DAY - 1, TICKS_PER_DAY), 1);
+        assert_eq!(escalation(DUE_AT, 8 * TICKS_PER_DAY, TICKS_PER_DAY), 2);
+    }
+
+    #[test]
+    fn due_day_gives_one_more_than_the_escalation_days() {
+        assert_eq!(
+            escalation(DUE_AT, DUE_AT, TICKS_PER_DAY),
+            ESCALATION_DAYS as u32 + 1
+        );
+    }
+
+    #[test]
+    fn overdue_days_keep_adding_one() {
+        let due_day = ESCALATION_DAYS as u32 + 1;
+
+        assert_eq!(
+            escalation(DUE_AT, DUE_AT + TICKS_PER_DAY, TICKS_PER_DAY),
+            due_day + 1
+        );
+        assert_eq!(
+            escalation(DUE_AT, DUE_AT + 5 * TICKS_PER_DAY + 3, TICKS_PER_DAY),
+            due_day + 5
+        );
+    }
+
+    #[test]
+    fn due_sooner_than_the_escalation_days_escalates_right_away() {
+        assert_eq!(escalation(TICKS_PER_DAY, 0, TICKS_PER_DAY), 1);
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{employee::EmployeeActor, task::TaskId};
+
+/// Most defects a single change can carry
+pub static MAX_DEFECTS_PER_CHANGE: u32 = 3;
+
+/// Chance for each of those defects to slip in when written by someone without any rigor, under
+/// full stress and without any focus left
+static MAX_DEFECT_CHANCE: f64 = 0.6;
+
+/// Chance for a reviewer with full rigor and focus to spot each defect of a change
+static MAX_CATCH_CHANCE: f64 = 0.9;
+
+/// Working days it takes for a defect that made it out to be noticed
+pub static SURFACE_DAYS: std::ops::RangeInclusive<u64> = 1..=5;
+
+/// A defect that made it out with a delivered work item, waiting to be noticed
+#[derive(Debug, Copy, Clone)]
+pub struct EscapedDefect {
+    /// Work item the defect was delivered with
+    pub origin: usize,
+    /// Tick it gets noticed on, a bug fix shows up on the board from then on
+    pub surfaces_at: u64,
+}
+
+/// What became of the defects written during a run
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DefectCounts {
+    /// Slipped into changes as they were written
+    pub introduced: u32,
+    /// Spotted in review or testing, before delivery
+    pub caught: u32,
+    /// Delivered along with their work item
+    pub escaped: u32,
+    /// Noticed after delivery and put on the board as bug fixes
+    pub surfaced: u32,
+}
+
+/// Stages where defects are looked for
+pub fn checks_for_defects(id: TaskId) -> bool {
+    matches!(id, TaskId::ReviewPR | TaskId::Test)
+}
+
+impl EmployeeActor {
+    /// Chance for every possible defect to slip into a change they write. Rigor keeps defects
+    /// out, stress and a lack of focus let them in.
+    pub fn defect_chance(&self) -> f64 {
+        let carelessness = 1. - self.characteristics.rigor as f64 / 100.;
+        let pressure = self.resources.stress as f64 / 100.;
+        let distraction = 1. - self.resources.focus as f64 / 100.;
+
+        let chance = MAX_DEFECT_CHANCE
+            * (0.2 + 0.8 * carelessness)
+            * (0.5 + 0.25 * pressure + 0.25 * distraction);
+        chance.clamp(0., 1.)
+    }
+
+    /// Chance to spot every defect of a change they review or test, thorough reviewers are
+    /// rigorous and focused.
+    pub fn catch_chance(&self) -> f64 {
+        let rigor = self.characteristics.rigor as f64 / 100.;
+        let focus = self.resources.focus as f64 / 100.;
+
+        (MAX_CATCH_CHANCE * rigor * (0.5 + 0.5 * focus)).clamp(0., 1.)
+    }
+}
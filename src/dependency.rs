@@ -0,0 +1,11 @@
+/// Working days another team takes to deliver what a work item waits on
+pub static OTHER_TEAM_DAYS: std::ops::RangeInclusive<u64> = 2..=8;
+
+/// Something a work item needs before anyone can start on it
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Dependency {
+    /// Another work item on the board, done once it is delivered
+    WorkItem(usize),
+    /// A delivery from another team, done from the tick it shows up on
+    OtherTeam { ready_at: u64 },
+}
@@ -0,0 +1,259 @@
+use std::fmt::{self, Display};
+
+use actix::{Handler, Message, MessageResult, SyncContext};
+use serde::Serialize;
+
+use crate::{
+    clock::SimClock,
+    employee::{EmployeeActor, EmployeeResources},
+};
+
+/// Timed effects an employee can be under
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+pub enum BuffId {
+    /// Had a coffee, crashes when it wears off
+    Caffeinated,
+    CaffeineCrash,
+    /// Slept too little after working late
+    SleepDeprived,
+    /// Focused and relaxed, work just flows
+    InTheZone,
+    /// Caught something, works slower and tires faster
+    Sick,
+}
+
+impl BuffId {
+    /// How long the effect lasts, in simulated minutes
+    fn duration_minutes(&self) -> u64 {
+        match self {
+            BuffId::Caffeinated => 3 * 60,
+            BuffId::CaffeineCrash => 2 * 60,
+            BuffId::SleepDeprived => 16 * 60,
+            BuffId::InTheZone => 2 * 60,
+            BuffId::Sick => 3 * 24 * 60,
+        }
+    }
+
+    /// How many times the effect can pile up, applying it again at the limit only makes it last
+    fn max_stacks(&self) -> u32 {
+        match self {
+            BuffId::Caffeinated => 3,
+            _ => 1,
+        }
+    }
+
+    /// Resources given or taken right away, halved for every stack already active and nothing once
+    /// at the limit
+    fn resources(&self) -> EmployeeResources {
+        let mut resources = EmployeeResources::empty();
+        match self {
+            BuffId::Caffeinated => {
+                resources.focus += 30.0;
+                resources.energy += 20.0;
+            }
+            BuffId::CaffeineCrash => resources.focus -= 15.0,
+            BuffId::SleepDeprived => resources.energy -= 20.0,
+            BuffId::InTheZone => resources.stress -= 5.0,
+            BuffId::Sick => resources.energy -= 30.0,
+        }
+
+        resources
+    }
+
+    /// Multiplier on the work done while the effect is active
+    fn output(&self) -> f32 {
+        match self {
+            BuffId::Caffeinated => 1.1,
+            BuffId::CaffeineCrash => 0.85,
+            BuffId::SleepDeprived => 0.8,
+            BuffId::InTheZone => 1.3,
+            BuffId::Sick => 0.5,
+        }
+    }
+
+    /// Multiplier on the energy and focus work costs while the effect is active
+    fn fatigue(&self) -> f32 {
+        match self {
+            BuffId::SleepDeprived => 1.2,
+            BuffId::Sick => 1.5,
+            _ => 1.,
+        }
+    }
+
+    /// Effect that follows once this one wears off
+    fn aftermath(&self) -> Option<BuffId> {
+        match self {
+            BuffId::Caffeinated => Some(BuffId::CaffeineCrash),
+            _ => None,
+        }
+    }
+}
+
+impl Display for BuffId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BuffId::Caffeinated => "Caffeinated",
+            BuffId::CaffeineCrash => "Caffeine crash",
+            BuffId::SleepDeprived => "Sleep deprived",
+            BuffId::InTheZone => "In the zone",
+            BuffId::Sick => "Sick",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ActiveEffect {
+    pub id: BuffId,
+    pub stacks: u32,
+    /// Tick the effect wears off on
+    pub until_tick: u64,
+}
+
+impl ActiveEffect {
+    pub fn ticks_left(&self, clock: &SimClock) -> u64 {
+        self.until_tick.saturating_sub(clock.ticks())
+    }
+}
+
+/// Effects an employee is under, at most one entry per effect
+#[derive(Debug, Clone, Default)]
+pub struct Effects(Vec<ActiveEffect>);
+
+impl Effects {
+    /// Starts the effect or stacks it on top of the active one, making it last its full duration
+    /// again. Returns the resources it gives, none when it was already at its limit.
+    fn apply(&mut self, id: BuffId, clock: &SimClock) -> EmployeeResources {
+        let until_tick = clock.ticks() + id.duration_minutes() / clock.minutes_per_tick();
+        let previous_stacks = match self.0.iter_mut().find(|effect| effect.id == id) {
+            Some(effect) => {
+                let previous_stacks = effect.stacks;
+                effect.stacks = (effect.stacks + 1).min(id.max_stacks());
+                effect.until_tick = until_tick;
+                previous_stacks
+            }
+            None => {
+                self.0.push(ActiveEffect {
+                    id,
+                    stacks: 1,
+                    until_tick,
+                });
+                0
+            }
+        };
+
+        if previous_stacks >= id.max_stacks() {
+            return EmployeeResources::empty();
+        }
+        let diminishing = 0.5_f32.powi(previous_stacks as i32);
+        let resources = id.resources();
+        EmployeeResources {
+            energy: resources.energy * diminishing,
+            focus: resources.focus * diminishing,
+            stress: resources.stress * diminishing,
+        }
+    }
+
+    /// Removes the effects that wore off, returns the ones that follow them.
+    fn expire(&mut self, clock: &SimClock) -> Vec<BuffId> {
+        let mut aftermath = vec![];
+        self.0.retain(|effect| {
+            let active = effect.until_tick > clock.ticks();
+            if !active {
+                aftermath.extend(effect.id.aftermath());
+            }
+            active
+        });
+
+        aftermath
+    }
+
+    pub fn is_active(&self, id: BuffId) -> bool {
+        self.0.iter().any(|effect| effect.id == id)
+    }
+
+    pub fn output(&self) -> f32 {
+        self.0.iter().map(|effect| effect.id.output()).product()
+    }
+
+    pub fn fatigue(&self) -> f32 {
+        self.0.iter().map(|effect| effect.id.fatigue()).product()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActiveEffect> {
+        self.0.iter()
+    }
+}
+
+impl EmployeeActor {
+    pub fn add_effect(&mut self, id: BuffId, clock: &SimClock) {
+        self.resources += self.effects.apply(id, clock);
+        self.resources.clamp();
+        self.applied_effects
+            .extend(self.effects.iter().find(|effect| effect.id == id).copied());
+    }
+
+    /// Drops the effects that wore off by `clock`, starting what follows them
+    pub fn update_effects(&mut self, clock: &SimClock) {
+        for id in self.effects.expire(clock) {
+            self.add_effect(id, clock);
+        }
+    }
+}
+
+/// An effect to come over an employee. Replies with the employee under it.
+pub struct Buff {
+    pub id: BuffId,
+    pub clock: SimClock,
+}
+
+impl Message for Buff {
+    type Result = EmployeeActor;
+}
+
+impl Handler<Buff> for EmployeeActor {
+    type Result = MessageResult<Buff>;
+
+    fn handle(&mut self, buff: Buff, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.update_effects(&buff.clock);
+        self.add_effect(buff.id, &buff.clock);
+
+        MessageResult(self.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacking_halves_what_every_effect_gives() {
+        let clock = SimClock::default();
+        let mut effects = Effects::default();
+
+        let focus = (0..4)
+            .map(|_| effects.apply(BuffId::Caffeinated, &clock).focus)
+            .collect::<Vec<_>>();
+
+        assert_eq!(focus, vec![30., 15., 7.5, 0.]);
+        assert_eq!(effects.iter().next().map(|effect| effect.stacks), Some(3));
+    }
+
+    #[test]
+    fn stacking_at_the_limit_only_makes_it_last() {
+        let mut clock = SimClock::default();
+        let mut effects = Effects::default();
+        effects.apply(BuffId::InTheZone, &clock);
+        clock.advance();
+
+        let resources = effects.apply(BuffId::InTheZone, &clock);
+
+        assert_eq!(resources.stress, 0.);
+        let effect = effects.iter().next().copied();
+        assert_eq!(
+            effect.map(|effect| effect.ticks_left(&clock)),
+            Some(BuffId::InTheZone.duration_minutes() / clock.minutes_per_tick())
+        );
+    }
+}
@@ -1,21 +1,76 @@
-use std::ops::AddAssign;
+use std::{
+    fmt::{self, Display},
+    ops::AddAssign,
+};
 
-use actix::{Actor, Addr, Handler, Message, SyncArbiter, SyncContext};
-use rand::Rng;
+use actix::{Actor, Addr, Handler, Message, MessageResult, SyncArbiter, SyncContext};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    clock::SimClock,
+    effect::{ActiveEffect, BuffId, Effects},
+    role::RoleDefinition,
+    stress::StressEvent,
     task::{TaskId, Work, WorkCompleted},
-    Kanban,
 };
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Energy an hour of rest gives back to the least fit employee, the fittest get `REST_FITNESS_BONUS`
+/// more
+static REST_ENERGY_PER_HOUR: f32 = 2.;
+static REST_FITNESS_BONUS: f32 = 6.;
+
+/// Energy an employee spends for every bit of energy they put into a task, focus goes twice as fast
+static FATIGUE: f32 = 3.;
+
+/// Energy cost of overtime work compared to the same work during working hours
+static OVERTIME_ENERGY_COST: f32 = 2.;
+
+/// Nights shorter than this leave employees sleep deprived
+static FULL_NIGHT_HOURS: u64 = 12;
+
+/// Chance to catch something over a night for the least fit employee
+static ILLNESS_CHANCE: f64 = 0.02;
+
+/// Chance on every tick of relaxed and focused work to get in the zone
+static IN_THE_ZONE_CHANCE: f64 = 0.05;
+
+/// Role of an employee, the stages each one works on come from the scenario
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Deserialize)]
 pub enum EmployeeType {
-    #[allow(unused)]
+    /// Doesn't write code, looks after the board and the team instead
     Manager,
     Developer,
+    QA,
+    Designer,
+    DevOps,
+    TechLead,
+}
+
+impl EmployeeType {
+    pub const ALL: [EmployeeType; 6] = [
+        EmployeeType::Manager,
+        EmployeeType::Developer,
+        EmployeeType::QA,
+        EmployeeType::Designer,
+        EmployeeType::DevOps,
+        EmployeeType::TechLead,
+    ];
+}
+
+/// Identifies an employee for the whole run, names can be shared
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EmployeeId(pub u32);
+
+impl Display for EmployeeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct EmployeeCharacteristics {
     pub company_experience: f32,
     pub rigor: f32,
@@ -23,15 +78,8 @@ pub struct EmployeeCharacteristics {
     pub fitness: f32,
 }
 
-impl Default for EmployeeCharacteristics {
-    fn default() -> Self {
-        EmployeeCharacteristics::new()
-    }
-}
-
 impl EmployeeCharacteristics {
-    pub fn new() -> EmployeeCharacteristics {
-        let mut rng = rand::thread_rng();
+    pub fn new<R: Rng>(rng: &mut R) -> EmployeeCharacteristics {
         EmployeeCharacteristics {
             company_experience: rng.gen_range(15.0..85.0),
             rigor: rng.gen_range(15.0..85.0),
@@ -41,7 +89,8 @@ impl EmployeeCharacteristics {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
 pub struct EmployeeResources {
     pub energy: f32,
     pub focus: f32,
@@ -70,6 +119,13 @@ impl EmployeeResources {
             stress: 0.0,
         }
     }
+
+    /// Keeps every resource between 0 and 100
+    pub fn clamp(&mut self) {
+        self.energy = self.energy.clamp(0., 100.);
+        self.focus = self.focus.clamp(0., 100.);
+        self.stress = self.stress.clamp(0., 100.);
+    }
 }
 
 impl AddAssign for EmployeeResources {
@@ -84,67 +140,152 @@ impl AddAssign for EmployeeResources {
 
 #[derive(Debug, Clone)]
 pub struct EmployeeActor {
-    pub employee_name: &'static str,
+    pub employee_id: EmployeeId,
+    pub employee_name: String,
     pub employee_type: EmployeeType,
+    pub role: RoleDefinition,
     pub characteristics: EmployeeCharacteristics,
     pub resources: EmployeeResources,
-    pub kanban_address: Addr<Kanban>,
+    /// Working days of sick leave left, nobody gives them work until then
+    pub sick_days: u64,
+    /// How many times stress sent them on sick leave
+    pub burnouts: u32,
+    pub effects: Effects,
+    /// Effects that came over them since they last reported to the kanban
+    pub applied_effects: Vec<ActiveEffect>,
+    /// Working days in a row on sick leave, skills slip after a few
+    pub days_away: u64,
+    /// Working days of onboarding left, a mentor looks after them until then
+    pub onboarding_days: u64,
+    /// Handed in their notice, they leave the team at the end of the tick
+    pub resigned: bool,
+    /// Per-employee random stream, seeded from the simulation seed
+    pub rng: StdRng,
 }
 
 impl EmployeeActor {
     pub fn new(
+        employee_id: EmployeeId,
         employee_type: EmployeeType,
-        name: &'static str,
+        role: RoleDefinition,
+        name: String,
         characteristics: EmployeeCharacteristics,
         resources: EmployeeResources,
-        kanban_address: Addr<Kanban>,
+        seed: u64,
     ) -> EmployeeActor {
         EmployeeActor {
+            employee_id,
             employee_name: name,
             employee_type,
+            role,
             characteristics,
             resources,
-            kanban_address,
+            sick_days: 0,
+            burnouts: 0,
+            effects: Effects::default(),
+            applied_effects: vec![],
+            days_away: 0,
+            onboarding_days: 0,
+            resigned: false,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
-    fn spawn_tasks(&self) {
-        let mut rng = rand::thread_rng();
+    /// Their current state for the kanban, which takes over the effects applied since the last one
+    pub fn report(&mut self) -> EmployeeActor {
+        let report = self.clone();
+        self.applied_effects.clear();
+        report
+    }
 
-        if self.resources.focus < 30. {
-            if rng.gen_bool(0.01) {
-                self.kanban_address.do_send(TaskId::CoffeeBreak.to_task());
-                self.kanban_address.do_send(TaskId::CoffeeBreak.to_task())
-            }
+    fn spawn_tasks(&mut self) -> Vec<TaskId> {
+        if self.resources.focus < 30. && self.rng.gen_bool(0.01) {
+            vec![TaskId::CoffeeBreak, TaskId::CoffeeBreak]
+        } else {
+            vec![]
         }
     }
 }
 
+#[cfg(test)]
+impl EmployeeActor {
+    /// Developer with the built-in role and every characteristic at 50, seeded with their id
+    pub fn developer(id: u32) -> EmployeeActor {
+        EmployeeActor::new(
+            EmployeeId(id),
+            EmployeeType::Developer,
+            crate::scenario::Scenario::builtin().role(EmployeeType::Developer),
+            format!("Developer {}", id),
+            EmployeeCharacteristics {
+                company_experience: 50.,
+                rigor: 50.,
+                programming_skills: 50.,
+                fitness: 50.,
+            },
+            EmployeeResources::new(),
+            id as u64,
+        )
+    }
+}
+
 impl Actor for EmployeeActor {
     type Context = SyncContext<Self>;
 }
 
 impl Handler<Work> for EmployeeActor {
-    type Result = ();
+    type Result = MessageResult<Work>;
 
-    fn handle(&mut self, work: Work, ctx: &mut SyncContext<Self>) -> Self::Result {
-        self.spawn_tasks();
+    fn handle(&mut self, work: Work, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.update_effects(&work.clock);
+        let spawned_tasks = self.spawn_tasks();
 
-        let task_data = work.task.to_task();
-        let multiplier = task_data.energy_multipliers.get_energy_cost(&self);
-        let energy_add = task_data.energy_taken_per_tick * multiplier;
+        let task_data = work.task;
+        let multiplier = task_data
+            .energy_multipliers
+            .get_energy_cost(&self.characteristics, &self.resources);
+        let mut energy_add = task_data.energy_taken_per_tick
+            * multiplier
+            * self.role.speed(task_data.id)
+            * self.effects.output();
 
-        self.resources.energy -= energy_add;
-        self.resources.focus -= energy_add * 2.;
+        let fatigue = energy_add * FATIGUE * self.effects.fatigue();
+        self.resources.energy -= if work.overtime {
+            fatigue * OVERTIME_ENERGY_COST
+        } else {
+            fatigue
+        };
+        self.resources.focus -= fatigue * 2.;
+        self.resources.clamp();
+
+        if work.overtime {
+            self.feel(StressEvent::Overtime);
+        }
+        for event in work.stress_events {
+            self.feel(event);
+        }
+        if self.makes_mistake() {
+            energy_add = -energy_add;
+        }
+        self.learn(&task_data, energy_add, work.pair);
+        self.check_burnout();
+
+        let relaxed = self.resources.focus > 80. && self.resources.stress < 20.;
+        if relaxed
+            && !self.effects.is_active(BuffId::InTheZone)
+            && self.rng.gen_bool(IN_THE_ZONE_CHANCE)
+        {
+            self.add_effect(BuffId::InTheZone, &work.clock);
+        }
 
-        self.kanban_address.do_send(WorkCompleted {
-            employee_address: ctx.address(),
-            employee_name: self.employee_name,
+        // Everything the kanban needs goes back in the reply so that it can apply the results of a
+        // tick in a fixed order, whatever order the employee threads finish in.
+        MessageResult(WorkCompleted {
+            employee_id: self.employee_id,
             uuid: work.uuid,
             energy_add,
-        });
-        // Send updated employee data
-        self.kanban_address.do_send(self.clone());
+            spawned_tasks,
+            employee_data: self.report(),
+        })
     }
 }
 
@@ -153,61 +294,54 @@ pub struct Employee {
 }
 
 impl Employee {
-    pub fn new(
-        employee_type: EmployeeType,
-        name: &'static str,
-        characteristics: EmployeeCharacteristics,
-        resources: EmployeeResources,
-        kanban_address: Addr<Kanban>,
-    ) -> Employee {
+    pub fn new(employee: EmployeeActor) -> Employee {
         Employee {
-            addr: SyncArbiter::start(1, move || {
-                EmployeeActor::new(
-                    employee_type,
-                    name,
-                    characteristics,
-                    resources,
-                    kanban_address.clone(),
-                )
-            }),
+            addr: SyncArbiter::start(1, move || employee.clone()),
         }
     }
 }
 
-pub enum BuffId {
-    Caffeinated,
+/// Time off between two days of work, sent when the next one starts. Replies with the rested
+/// employee.
+pub struct Rest {
+    pub hours: u64,
+    /// A weekend went by
+    pub weekend: bool,
+    pub clock: SimClock,
 }
 
-impl BuffId {
-    fn translate_to_resources(&self) -> EmployeeResources {
-        match *self {
-            BuffId::Caffeinated => {
-                let mut bonus_resources = EmployeeResources::empty();
-                bonus_resources.focus += 30.0;
-                bonus_resources.energy += 20.0;
-
-                bonus_resources
-            }
-        }
-    }
+impl Message for Rest {
+    type Result = EmployeeActor;
 }
 
-pub struct Buff {
-    pub id: BuffId,
-}
+impl Handler<Rest> for EmployeeActor {
+    type Result = MessageResult<Rest>;
 
-impl Message for Buff {
-    type Result = ();
-}
+    fn handle(&mut self, rest: Rest, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.update_effects(&rest.clock);
 
-impl Handler<Buff> for EmployeeActor {
-    type Result = ();
+        let per_hour =
+            REST_ENERGY_PER_HOUR + REST_FITNESS_BONUS * self.characteristics.fitness / 100.;
+        self.resources.energy += per_hour * rest.hours as f32;
+        self.resources.focus = 100.;
+        self.resources.clamp();
 
-    fn handle(&mut self, buff: Buff, _ctx: &mut SyncContext<Self>) -> Self::Result {
-        self.resources += buff.id.translate_to_resources();
-    }
-}
+        if rest.weekend {
+            self.feel(StressEvent::Weekend);
+        }
+        self.track_absence();
+        self.recover();
+        self.settle_in();
+        self.consider_quitting();
 
-impl Message for EmployeeActor {
-    type Result = ();
+        if rest.hours < FULL_NIGHT_HOURS {
+            self.add_effect(BuffId::SleepDeprived, &rest.clock);
+        }
+        let illness_chance = ILLNESS_CHANCE * (1. - self.characteristics.fitness as f64 / 100.);
+        if self.rng.gen_bool(illness_chance.clamp(0., 1.)) {
+            self.add_effect(BuffId::Sick, &rest.clock);
+        }
+
+        MessageResult(self.report())
+    }
 }
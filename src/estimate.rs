@@ -0,0 +1,40 @@
+use std::ops::RangeInclusive;
+
+use rand::{rngs::StdRng, Rng};
+
+/// True size of a work item, as a multiple of a typical one of its kind, which is also the average
+pub static SIZE_RANGE: RangeInclusive<f32> = 0.5..=1.5;
+
+/// Most an estimate is off by, as a share of the true size, for a team that knows nothing about
+/// the company
+static MAX_ESTIMATE_ERROR: f32 = 0.6;
+
+/// Story points of a work item of typical size
+static TYPICAL_POINTS: f32 = 3.;
+
+/// Story points estimates are rounded to
+static POINTS: [u32; 6] = [1, 2, 3, 5, 8, 13];
+
+/// Size a team with an average company experience of `experience` expects a work item of true
+/// `size` to be. The less they know the company the further off they are, and they are too
+/// optimistic more often than not.
+pub fn estimate(size: f32, experience: f32, rng: &mut StdRng) -> f32 {
+    let spread = MAX_ESTIMATE_ERROR * (1. - experience / 100.).clamp(0., 1.);
+    let error = rng.gen_range(-spread..=spread / 2.);
+
+    size * (1. + error)
+}
+
+/// Story points of a work item of `size`, the closest of the usual ones
+pub fn story_points(size: f32) -> u32 {
+    let points = size * TYPICAL_POINTS;
+
+    POINTS
+        .into_iter()
+        .min_by(|a, b| {
+            (*a as f32 - points)
+                .abs()
+                .total_cmp(&(*b as f32 - points).abs())
+        })
+        .unwrap_or(1)
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use serde::Serialize;
+
+use crate::{
+    column::Column,
+    effect::BuffId,
+    employee::{EmployeeCharacteristics, EmployeeId, EmployeeResources},
+    manager::ManagerAction,
+    review::ReviewOutcome,
+    task::TaskId,
+    workflow::WorkItemKind,
+};
+
+/// Something that changed in the simulation, stamped with the simulated time it happened at
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    pub tick: u64,
+    /// Simulated time as shown on the board
+    pub time: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    /// A task was put on the board, a new work item or the next stage of one
+    TaskCreated {
+        uuid: usize,
+        task: TaskId,
+        kind: WorkItemKind,
+        stage: usize,
+        rework: bool,
+        /// Delivered work item a bug fix is for
+        fixes: Option<usize>,
+        /// What the task really needs, the team only knows `estimated_energy`
+        energy_required: f32,
+        estimated_energy: f32,
+    },
+    /// The team estimated a new work item, sizes are multiples of a typical one of its kind
+    WorkItemEstimated {
+        uuid: usize,
+        story_points: u32,
+        estimate: f32,
+        /// True size, unknown to the team
+        size: f32,
+    },
+    /// The scheduler gave a task to an employee for a tick
+    TaskAssigned {
+        uuid: usize,
+        task: TaskId,
+        employee: EmployeeId,
+    },
+    /// An employee did a tick of work on a task
+    TaskProgressed {
+        uuid: usize,
+        task: TaskId,
+        employee: EmployeeId,
+        energy: f32,
+        energy_taken: f32,
+        energy_required: f32,
+    },
+    TaskCompleted {
+        uuid: usize,
+        task: TaskId,
+        contributors: BTreeSet<EmployeeId>,
+    },
+    /// A work item finished a stage, but the column of its next one is full so it waits for room
+    WorkItemWaiting {
+        uuid: usize,
+        task: TaskId,
+        column: Column,
+    },
+    /// A work item made it through its whole workflow
+    WorkItemDelivered {
+        uuid: usize,
+        kind: WorkItemKind,
+        /// Ticks since it showed up on the board
+        lead_time: u64,
+    },
+    /// Reviewers decided what becomes of the change of a work item
+    ReviewFinished {
+        uuid: usize,
+        outcome: ReviewOutcome,
+        reviewers: BTreeSet<EmployeeId>,
+        /// How many times the work item was reviewed so far, this one included
+        round: u32,
+    },
+    /// Defects slipped into the change of a work item as it was written
+    DefectsIntroduced {
+        uuid: usize,
+        count: u32,
+        authors: BTreeSet<EmployeeId>,
+    },
+    /// Defects spotted in a review or a test, before delivery
+    DefectsCaught {
+        uuid: usize,
+        task: TaskId,
+        count: u32,
+        reviewers: BTreeSet<EmployeeId>,
+    },
+    /// A work item was delivered with defects nobody spotted
+    DefectsEscaped {
+        uuid: usize,
+        count: u32,
+    },
+    /// Something outside the team held up a started task
+    ImpedimentRaised {
+        uuid: usize,
+        task: TaskId,
+    },
+    /// An impediment went away, cleared by a manager or on its own
+    ImpedimentCleared {
+        uuid: usize,
+        task: TaskId,
+        manager: Option<EmployeeId>,
+    },
+    /// A manager triaged the backlog, cleared an impediment or called a meeting
+    ManagerActed {
+        manager: EmployeeId,
+        action: ManagerAction,
+    },
+    /// An employee came under an effect, or got one of theirs renewed or stacked
+    BuffApplied {
+        employee: EmployeeId,
+        buff: BuffId,
+        stacks: u32,
+        until_tick: u64,
+    },
+    /// Latest state an employee reported, after resting, working or managing
+    EmployeeUpdated {
+        employee: EmployeeId,
+        characteristics: EmployeeCharacteristics,
+        resources: EmployeeResources,
+        sick_days: u64,
+    },
+    EmployeeJoined {
+        employee: EmployeeId,
+        name: String,
+    },
+    EmployeeLeft {
+        employee: EmployeeId,
+    },
+}
+
+/// Gets every event of a run as it happens, in the order the kanban applies them
+pub trait EventSubscriber: Send {
+    fn notify(&mut self, event: &Event);
+}
+
+impl EventSubscriber for Sender<Event> {
+    fn notify(&mut self, event: &Event) {
+        // Nobody listening any more is not a reason to fail
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Writes every event coming out of `events` as a line of JSON, until the run is over.
+pub fn write_json_lines<W: Write>(events: Receiver<Event>, mut writer: W) -> io::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut writer, &event)?;
+        writeln!(writer)?;
+    }
+
+    writer.flush()
+}
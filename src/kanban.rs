@@ -1,583 +1,1534 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
-    io::{stdout, Stdout, Write},
-    sync::atomic::AtomicUsize,
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque},
+    sync::mpsc::Sender,
     time::Duration,
 };
 
-use actix::{Actor, Addr, AsyncContext, Context, Handler, Message, System};
-use chrono::{DateTime, Utc};
-use crossterm::{
-    cursor, event,
-    event::{poll, Event, KeyCode, KeyEvent},
-    queue, style,
-    style::{Color, Stylize},
-    terminal,
-    terminal::enable_raw_mode,
-    ExecutableCommand,
+use actix::{
+    Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, Context, Handler, Message,
+    SpawnHandle, System, WrapFuture,
 };
-use rand::Rng;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
 use crate::{
-    employee::{Buff, BuffId, EmployeeActor},
+    avatar,
+    clock::{DayPhase, Pace, SimClock},
+    column::{Column, WipLimits},
+    deadline::escalation,
+    defect::{
+        checks_for_defects, DefectCounts, EscapedDefect, MAX_DEFECTS_PER_CHANGE, SURFACE_DAYS,
+    },
+    dependency::{Dependency, OTHER_TEAM_DAYS},
+    effect::{Buff, BuffId},
+    employee::{Employee, EmployeeActor, EmployeeCharacteristics, EmployeeId, EmployeeType, Rest},
+    estimate::{estimate, story_points, SIZE_RANGE},
+    event::{Event, EventKind, EventSubscriber},
+    learning::SkillSample,
+    manager::{BacklogItem, Manage, ManagementDone, ManagerAction, Meeting},
+    review::{review_outcome, ReviewCounts, ReviewOutcome, REWORK_SIZE},
+    scenario::{EmployeeSpec, Scenario},
+    scheduler::{AssignmentStrategy, Board},
+    staffing::{Mentoring, MENTORING_FOCUS_COST, NEW_HIRE_EXPERIENCE, ONBOARDING_DAYS},
+    stress::{Stress, StressEvent},
+    summary::Summary,
     task::{Task, TaskId, Work, WorkCompleted},
+    tui::{KeyAction, Tui},
+    workflow::{WorkItem, WorkItemKind},
 };
 
-static TICK_RATE: f32 = 10.;
+pub type TaskEntry = (usize, (Task, BTreeSet<EmployeeId>));
 
-pub fn create_task_id() -> usize {
-    static COUNTER: AtomicUsize = AtomicUsize::new(1);
-    COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+/// Chance on every tick for an impediment to go away without a manager
+static IMPEDIMENT_FADE_CHANCE: f64 = 0.005;
+
+/// When a run should stop on its own
+#[derive(Debug, Copy, Clone)]
+pub enum RunLength {
+    Ticks(u64),
+    Days(u64),
+}
+
+impl RunLength {
+    pub fn in_ticks(&self, clock: &SimClock) -> u64 {
+        match *self {
+            RunLength::Ticks(ticks) => ticks,
+            RunLength::Days(days) => days * clock.ticks_per_day(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Run without a terminal attached
+    pub headless: bool,
+    /// How fast ticks follow each other at the start of the run
+    pub pace: Pace,
+    /// Stop after this long, run until quit otherwise
+    pub run_length: Option<RunLength>,
 }
 
 pub struct Kanban {
-    stdout: Stdout,
-    task_list: HashMap<usize, (Task, BTreeSet<String>)>,
-    done_list: VecDeque<(usize, Task, BTreeSet<String>)>,
-    pub employee_addresses: Vec<Addr<EmployeeActor>>,
-    employee_data: BTreeMap<String, EmployeeActor>,
-    curr_employee: usize,
-    start_time: DateTime<Utc>,
-    okko: &'static str,
-    anton: &'static str
+    /// Seed the simulation was started with, shown so that a run can be reproduced
+    pub seed: u64,
+    /// Random stream for every decision the kanban takes
+    rng: StdRng,
+    scenario: Scenario,
+    scheduler: Box<dyn AssignmentStrategy>,
+    next_task_id: usize,
+    pub task_list: BTreeMap<usize, (Task, BTreeSet<EmployeeId>)>,
+    pub done_list: VecDeque<(usize, Task, BTreeSet<EmployeeId>)>,
+    /// How many tasks of each kind have been finished since the start
+    pub completed_tasks: BTreeMap<TaskId, usize>,
+    /// Work items still going through their workflow, by uuid
+    pub work_items: BTreeMap<usize, WorkItem>,
+    /// Work items that made it through their whole workflow, in delivery order
+    pub delivered: Vec<WorkItem>,
+    /// What became of the defects written so far
+    pub defects: DefectCounts,
+    /// Outcomes of the reviews done so far
+    pub reviews: ReviewCounts,
+    /// Defects delivered but not noticed yet, in delivery order
+    escaped_defects: Vec<EscapedDefect>,
+    /// Effects the current tick brought on employees, handed out once everyone answered
+    buffs: Vec<(EmployeeId, BuffId)>,
+    pub employee_addresses: BTreeMap<EmployeeId, Addr<EmployeeActor>>,
+    /// Latest known state of every employee, by id
+    pub employee_data: BTreeMap<EmployeeId, EmployeeActor>,
+    /// Last known state of the employees who left, and the day they left on, by id
+    pub former_employees: BTreeMap<EmployeeId, (u64, EmployeeActor)>,
+    /// Who looks after every new hire still onboarding, by id of the new hire
+    pub mentors: BTreeMap<EmployeeId, EmployeeId>,
+    /// Id the next hire gets
+    next_employee_id: EmployeeId,
+    /// Index of the next staffing event of the scenario to happen
+    next_staffing: usize,
+    /// Characteristics of every employee at the start of every day, by id
+    pub skill_history: BTreeMap<EmployeeId, Vec<SkillSample>>,
+    /// ASCII art of every employee, by id
+    pub avatars: BTreeMap<EmployeeId, String>,
+    pub clock: SimClock,
+    /// Part of the day the last tick was in
+    pub day_phase: DayPhase,
+    /// Ticks off work since the end of the last day of work
+    off_ticks: u64,
+    pub pace: Pace,
+    /// Pace to go back to when the simulation is unpaused
+    resume_pace: Pace,
+    /// Tick scheduled by the current pace, cancelled when pausing
+    next_tick: Option<SpawnHandle>,
+    options: RunOptions,
+    tui: Option<Tui>,
+    /// Where the summary goes once the run is over
+    report: Option<Sender<Summary>>,
+    /// Told about everything that changes during the run
+    subscribers: Vec<Box<dyn EventSubscriber>>,
 }
 
 impl Kanban {
-    pub fn new() -> Self {
-        enable_raw_mode().unwrap();
-
-        let mut stdout = stdout();
-        stdout
-            .execute(terminal::Clear(terminal::ClearType::All))
-            .unwrap();
-
-        // Reset terminal
-        queue!(
-            stdout,
-            terminal::EnterAlternateScreen,
-            style::ResetColor,
-            terminal::Clear(terminal::ClearType::All),
-            cursor::MoveTo(1, 1),
-            cursor::Show,
-            cursor::EnableBlinking
-        )
-        .unwrap();
-
-        stdout.flush().unwrap();
-        let time = Utc::now();
-        Kanban {
-            stdout,
-            task_list: HashMap::new(),
+    pub fn new(
+        seed: u64,
+        rng: StdRng,
+        scenario: Scenario,
+        options: RunOptions,
+        report: Sender<Summary>,
+        subscribers: Vec<Box<dyn EventSubscriber>>,
+    ) -> Self {
+        let tui = if options.headless {
+            None
+        } else {
+            Some(Tui::new())
+        };
+
+        let mut kanban = Kanban {
+            seed,
+            rng,
+            clock: SimClock::new(scenario.minutes_per_tick),
+            day_phase: DayPhase::Off,
+            off_ticks: 0,
+            scheduler: scenario.scheduler.strategy(),
+            // The starting team is numbered in scenario order
+            next_employee_id: EmployeeId(scenario.employees.len() as u32),
+            scenario,
+            next_task_id: 1,
+            task_list: BTreeMap::new(),
             done_list: VecDeque::new(),
-            employee_addresses: vec![],
+            completed_tasks: BTreeMap::new(),
+            work_items: BTreeMap::new(),
+            delivered: vec![],
+            defects: DefectCounts::default(),
+            reviews: ReviewCounts::default(),
+            escaped_defects: vec![],
+            buffs: vec![],
+            employee_addresses: BTreeMap::new(),
             employee_data: BTreeMap::new(),
-            curr_employee: 0,
-            start_time: time,
-            okko: include_str!("../okko.txt"),
-            anton: include_str!("../anton.txt")
+            former_employees: BTreeMap::new(),
+            mentors: BTreeMap::new(),
+            next_staffing: 0,
+            skill_history: BTreeMap::new(),
+            avatars: BTreeMap::new(),
+            pace: options.pace,
+            resume_pace: Pace::Speed(1.),
+            next_tick: None,
+            options,
+            tui,
+            report: Some(report),
+            subscribers,
+        };
+
+        for _ in 0..kanban.scenario.spawn.initial_features {
+            kanban.add_work_item(WorkItemKind::Feature, None);
         }
+        for _ in 0..kanban.scenario.spawn.initial_bug_fixes {
+            kanban.add_work_item(WorkItemKind::BugFix, None);
+        }
+
+        kanban
     }
 
     pub fn tick(&mut self, context: &mut Context<Kanban>) {
-        self.handle_keys();
-
-        let mut rng = rand::thread_rng();
+        self.next_tick = None;
+        self.apply_staffing(context);
 
-        if rng.gen_bool(0.01) && self.task_list.len() < 10 {
-            let task = if rng.gen_bool(0.2) {
-                TaskId::CreatePR.to_task().as_bug_fix()
+        let spawn = self.scenario.spawn;
+        if self.rng.gen_bool(spawn.chance_per_tick) && self.has_room(Column::Backlog, None) {
+            let kind = if self.rng.gen_bool(spawn.chore_chance) {
+                WorkItemKind::Chore
             } else {
-                TaskId::CreatePR.to_task().as_feature()
+                WorkItemKind::Feature
             };
 
-            context.notify(task);
+            let uuid = self.add_work_item(kind, None);
+            self.declare_dependencies(uuid);
+        }
+        self.surface_defects();
+        self.estimate_work_items();
+
+        self.move_waiting_work_items();
+        self.update_impediments();
+
+        let day_phase = self.scenario.working_hours.phase(&self.clock);
+        let mut rest_requests = vec![];
+        if day_phase == DayPhase::Off {
+            self.off_ticks += 1;
+        } else if self.day_phase == DayPhase::Off {
+            // Everyone starts the run rested, time off only counts once a day was worked
+            if self.off_ticks < self.clock.ticks() {
+                let hours = self.off_ticks * self.clock.minutes_per_tick() / 60;
+                rest_requests = self
+                    .employee_addresses
+                    .values()
+                    .map(|address| {
+                        address.send(Rest {
+                            hours,
+                            weekend: hours >= 24,
+                            clock: self.clock,
+                        })
+                    })
+                    .collect();
+            }
+            self.off_ticks = 0;
         }
+        self.day_phase = day_phase;
+
+        let task_list = self.assignable_tasks();
+        let board = Board {
+            tasks: &task_list,
+            work_items: &self.work_items,
+            employees: self
+                .employee_data
+                .values()
+                .filter(|employee| employee.role.takes_tasks() && employee.sick_days == 0)
+                .collect(),
+        };
+        let assignments = if day_phase == DayPhase::Off {
+            vec![]
+        } else {
+            self.scheduler.assign(&board, &mut self.rng)
+        };
 
-        let mut task_list = self
-            .task_list
-            .clone()
-            .into_iter()
-            .map(|(uuid, val)| (uuid, val))
-            .collect::<Vec<(usize, (Task, BTreeSet<String>))>>();
-        task_list.sort_by(|a, b| {
-            let task_a: Task = a.1 .0;
-            let task_b: Task = b.1 .0;
+        for (employee_id, uuid) in assignments.iter() {
+            if let Some((task, _)) = self.task_list.get(uuid) {
+                self.emit(EventKind::TaskAssigned {
+                    uuid: *uuid,
+                    task: task.id,
+                    employee: *employee_id,
+                });
+            }
+        }
 
-            if task_b.id.priority() == task_a.id.priority() {
-                b.1 .0.progress().partial_cmp(&a.1 .0.progress()).unwrap()
-            } else {
-                task_b.id.priority().cmp(&task_a.id.priority())
+        let requests = assignments
+            .into_iter()
+            .filter_map(|(employee_id, uuid)| {
+                let employee_address = self.employee_addresses.get(&employee_id)?;
+                let (task, contributors) = self.task_list.get(&uuid)?;
+                let mentor = self
+                    .employee_data
+                    .get(&employee_id)
+                    .filter(|employee| employee.is_onboarding())
+                    .and_then(|_| self.mentors.get(&employee_id));
+                if let Some(mentor_address) =
+                    mentor.and_then(|mentor| self.employee_addresses.get(mentor))
+                {
+                    mentor_address.do_send(Mentoring {
+                        focus_cost: MENTORING_FOCUS_COST,
+                    });
+                }
+                let pair = contributors
+                    .iter()
+                    .chain(mentor)
+                    .filter(|id| **id != employee_id)
+                    .filter_map(|id| self.employee_data.get(id))
+                    .map(|employee| employee.characteristics)
+                    .max_by(|a, b| {
+                        let skill = |c: &EmployeeCharacteristics| {
+                            c.programming_skills + c.company_experience
+                        };
+                        skill(a).total_cmp(&skill(b))
+                    });
+                let mut stress_events = vec![];
+                // A full backlog is only work waiting, not work in progress
+                if Column::of(task)
+                    .filter(|column| *column != Column::Backlog)
+                    .is_some_and(|column| !self.has_room(column, None))
+                {
+                    stress_events.push(StressEvent::WipOverload);
+                }
+                if self.is_overdue(uuid) {
+                    stress_events.push(StressEvent::Overdue);
+                }
+                Some(employee_address.send(Work {
+                    task: *task,
+                    uuid,
+                    overtime: day_phase == DayPhase::Overtime,
+                    stress_events,
+                    clock: self.clock,
+                    pair,
+                }))
+            })
+            .collect::<Vec<_>>();
+
+        let manage_requests = self
+            .employee_data
+            .values()
+            .filter(|employee| employee.employee_type == EmployeeType::Manager)
+            .filter(|manager| manager.sick_days == 0 && day_phase == DayPhase::Working)
+            .filter_map(|manager| self.employee_addresses.get(&manager.employee_id))
+            .map(|manager_address| manager_address.send(self.manage()))
+            .collect::<Vec<_>>();
+
+        // Hold every other message until all employees have answered, and apply their answers in
+        // assignment order, so that a seed always replays the same board history.
+        context.wait(
+            async move {
+                let mut rested = vec![];
+                for request in rest_requests {
+                    if let Ok(employee_data) = request.await {
+                        rested.push(employee_data);
+                    }
+                }
+                let mut reports = vec![];
+                for request in requests {
+                    if let Ok(report) = request.await {
+                        reports.push(report);
+                    }
+                }
+                let mut management = vec![];
+                for request in manage_requests {
+                    if let Ok(management_done) = request.await {
+                        management.push(management_done);
+                    }
+                }
+                (rested, reports, management)
             }
-        });
+            .into_actor(self)
+            .map(|(rested, reports, management), kanban, context| {
+                for employee_data in rested {
+                    kanban.record_skills(&employee_data);
+                    kanban.update_employee(employee_data);
+                }
+                for work_completed in reports {
+                    kanban.complete_work(work_completed);
+                }
+                for management_done in management {
+                    kanban.complete_management(management_done);
+                }
+                kanban.hand_out_buffs(context);
+            }),
+        );
+    }
 
-        for (index, employee_address) in self.employee_addresses.iter().enumerate() {
-            if let Some((j, (task, _c))) = task_list.iter().nth(index) {
-                employee_address.do_send(Work {
-                    task: task.id,
-                    uuid: *j,
-                })
+    /// Puts the employees under the effects the tick brought, and waits for all of them to answer
+    /// before ending the tick so that the effects are known on the tick they came on.
+    fn hand_out_buffs(&mut self, context: &mut Context<Kanban>) {
+        let requests = self
+            .buffs
+            .drain(..)
+            .filter_map(|(employee_id, id)| {
+                let address = self.employee_addresses.get(&employee_id)?;
+                Some(address.send(Buff {
+                    id,
+                    clock: self.clock,
+                }))
+            })
+            .collect::<Vec<_>>();
+
+        context.wait(
+            async move {
+                let mut buffed = vec![];
+                for request in requests {
+                    if let Ok(employee_data) = request.await {
+                        buffed.push(employee_data);
+                    }
+                }
+                buffed
             }
+            .into_actor(self)
+            .map(|buffed, kanban, context| {
+                for employee_data in buffed {
+                    kanban.update_employee(employee_data);
+                }
+                kanban.end_tick(context);
+            }),
+        );
+    }
+
+    /// Lets go of the employees who resigned and moves the clock on to the next tick, unless the
+    /// run is over.
+    fn end_tick(&mut self, context: &mut Context<Kanban>) {
+        let resigned = self
+            .employee_data
+            .values()
+            .filter(|employee| employee.resigned)
+            .map(|employee| employee.employee_id)
+            .collect::<Vec<_>>();
+        for employee_id in resigned {
+            self.fire(employee_id);
+        }
+        self.clock.advance();
+        self.draw();
+
+        let run_over = self
+            .options
+            .run_length
+            .is_some_and(|run_length| self.clock.ticks() >= run_length.in_ticks(&self.clock));
+        if run_over {
+            self.finish(context);
+        } else {
+            self.schedule_tick(context);
+        }
+    }
+
+    /// Schedules the next tick according to the current pace, stepping waits for a key instead.
+    fn schedule_tick(&mut self, context: &mut Context<Kanban>) {
+        if let Some(interval) = self.pace.tick_interval() {
+            self.next_tick =
+                Some(context.run_later(interval, |kanban, context| kanban.tick(context)));
+        }
+    }
+
+    fn draw(&mut self) {
+        if let Some(mut tui) = self.tui.take() {
+            tui.draw(self);
+            self.tui = Some(tui);
         }
-        self.draw(&task_list)
     }
 
-    fn handle_keys(&mut self) {
-        if poll(Duration::from_millis(20)).unwrap() {
-            let event = event::read();
-            if let Ok(Event::Key(KeyEvent {
-                code: KeyCode::Left,
-                ..
-            })) = event
-            {
-                if self.curr_employee == 0 {
-                    self.curr_employee = self.employee_addresses.len() - 1;
+    fn handle_keys(&mut self, context: &mut Context<Kanban>) {
+        let action = match self.tui.as_mut() {
+            Some(tui) => tui.handle_keys(self.employee_data.len()),
+            None => return,
+        };
+
+        match action {
+            Some(KeyAction::Quit) => {
+                self.finish(context);
+                return;
+            }
+            Some(KeyAction::Step) if self.pace == Pace::Step => self.tick(context),
+            Some(KeyAction::TogglePause) => {
+                if self.pace == Pace::Step {
+                    self.pace = self.resume_pace;
+                    self.schedule_tick(context);
                 } else {
-                    self.curr_employee -= 1;
+                    self.resume_pace = self.pace;
+                    self.pace = Pace::Step;
+                    if let Some(handle) = self.next_tick.take() {
+                        context.cancel_future(handle);
+                    }
                 }
             }
-            if let Ok(Event::Key(KeyEvent {
-                code: KeyCode::Right,
-                ..
-            })) = event
-            {
-                self.curr_employee = (self.curr_employee + 1) % self.employee_addresses.len();
-            }
-            if let Ok(Event::Key(KeyEvent {
-                code: KeyCode::Esc,
-                ..
-            })) = event
-            {
-                queue!(
-                    self.stdout,
-                    terminal::LeaveAlternateScreen,
-                    style::ResetColor,
-                    terminal::Clear(terminal::ClearType::All),
-                    cursor::MoveTo(0, 0),
-                    cursor::Show,
-                    cursor::EnableBlinking
-                )
-                .unwrap();
-                self.stdout.flush().unwrap();
-                System::current().stop();
-            }
-        }
-    }
-
-    fn draw(&mut self, sorted_task_list: &Vec<(usize, (Task, BTreeSet<String>))>) {
-        let max_bar_width = 15;
-        let progress_color = Color::Green;
-        let done_color = Color::Blue;
-        queue!(
-            self.stdout,
-            terminal::Clear(terminal::ClearType::All),
-            cursor::MoveTo(0, 0),
-        )
-        .unwrap();
-        let employees = self.employee_data.keys().cloned().collect::<Vec<String>>();
-        if let Some(employee_name) = employees.get(self.curr_employee) {
-            let curr_employee = self.employee_data.get(employee_name).unwrap();
-            let mut employee_tasks = vec![];
-            for (_id, (task, contributors)) in self.task_list.iter() {
-                if contributors.contains(curr_employee.employee_name) {
-                    employee_tasks.push(task.clone());
-                }
+            Some(KeyAction::Faster) => self.pace = self.pace.faster(),
+            Some(KeyAction::Slower) => self.pace = self.pace.slower(),
+            _ => {}
+        }
+
+        if action.is_some() {
+            self.draw();
+        }
+    }
+
+    /// Restores the terminal, reports the summary and stops the system.
+    fn finish(&mut self, context: &mut Context<Kanban>) {
+        self.tui = None;
+
+        if let Some(report) = self.report.take() {
+            // Nobody listening any more is not a reason to fail
+            let _ = report.send(Summary::new(self));
+        }
+
+        context.stop();
+        System::current().stop();
+    }
+
+    fn add_task(&mut self, task: Task) {
+        self.insert_task(self.next_task_id, task);
+        self.next_task_id += 1;
+    }
+
+    /// Puts `task` on the board under `uuid`, in place of whatever stage was there before
+    fn insert_task(&mut self, uuid: usize, task: Task) {
+        self.task_list.insert(uuid, (task, BTreeSet::new()));
+        self.emit(EventKind::TaskCreated {
+            uuid,
+            task: task.id,
+            kind: task.kind,
+            stage: task.stage,
+            rework: task.rework,
+            fixes: self.fixes(uuid),
+            energy_required: task.total_energy_required,
+            estimated_energy: task.estimated_energy,
+        });
+    }
+
+    /// Tells every subscriber about something that just changed
+    fn emit(&mut self, kind: EventKind) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+
+        let event = Event {
+            tick: self.clock.ticks(),
+            time: self.clock.to_string(),
+            kind,
+        };
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.notify(&event);
+        }
+    }
+
+    /// Puts the first stage of a new work item on the board, due in the days the scenario gives
+    /// its kind and of a size nobody knows yet. Bug fixes tell which delivered work item they
+    /// fix. Returns its uuid.
+    fn add_work_item(&mut self, kind: WorkItemKind, fixes: Option<usize>) -> usize {
+        let uuid = self.next_task_id;
+        let now = self.clock.ticks();
+        let work_item = WorkItem {
+            due_at: self
+                .scenario
+                .due_days
+                .days(kind)
+                .map(|days| now + days * self.clock.ticks_per_day()),
+            size: self.rng.gen_range(SIZE_RANGE.clone()),
+            fixes,
+            ..WorkItem::new(kind, now)
+        };
+        self.work_items.insert(uuid, work_item);
+        self.add_task(self.sized(uuid, self.scenario.work_item(kind)));
+
+        uuid
+    }
+
+    /// `task` of the work item with `uuid`, needing energy for the true size of the work item and
+    /// expected to need it for the estimated one
+    fn sized(&self, uuid: usize, task: Task) -> Task {
+        let Some(work_item) = self.work_items.get(&uuid) else {
+            return task;
+        };
+
+        Task {
+            total_energy_required: task.total_energy_required * work_item.size,
+            estimated_energy: task.total_energy_required * work_item.estimate.unwrap_or(1.),
+            ..task
+        }
+    }
+
+    /// Has the team estimate the work items that showed up since the last tick, the more they know
+    /// the company the closer they get to the true size.
+    fn estimate_work_items(&mut self) {
+        let team = self
+            .employee_data
+            .values()
+            .filter(|employee| employee.role.takes_tasks())
+            .map(|employee| employee.characteristics.company_experience)
+            .collect::<Vec<_>>();
+        if team.is_empty() {
+            return;
+        }
+        let experience = team.iter().sum::<f32>() / team.len() as f32;
+
+        let mut estimated = vec![];
+        for (uuid, work_item) in self.work_items.iter_mut() {
+            if work_item.estimate.is_some() {
+                continue;
             }
-            draw_employee_card(&mut self.stdout, curr_employee, &employee_tasks, &self.okko, &self.anton);
+            let guess = estimate(work_item.size, experience, &mut self.rng);
+            work_item.estimate = Some(guess);
+            if let Some((task, _)) = self.task_list.get_mut(uuid) {
+                task.estimated_energy = task.total_energy_required / work_item.size * guess;
+            }
+            estimated.push(EventKind::WorkItemEstimated {
+                uuid: *uuid,
+                story_points: story_points(guess),
+                estimate: guess,
+                size: work_item.size,
+            });
+        }
+        for event in estimated {
+            self.emit(event);
         }
+    }
 
-        // List all employees
-        let employess = BTreeSet::from_iter(employees);
-        queue!(self.stdout, style::Print("Super Dev Organization: ".red()),).unwrap();
-        draw_contributors(&mut self.stdout, &employess);
-        queue!(self.stdout, cursor::MoveToNextLine(1),).unwrap();
+    /// Story points the team gave the work item with `uuid`, `None` until they estimated it
+    pub fn story_points(&self, uuid: usize) -> Option<u32> {
+        self.work_items
+            .get(&uuid)
+            .and_then(|work_item| work_item.estimate)
+            .map(story_points)
+    }
 
-        draw_time_bar(&mut self.stdout, self.start_time);
+    /// Rolls what a new work item has to wait for: one of the work items already on the board, a
+    /// delivery from another team, or both.
+    fn declare_dependencies(&mut self, uuid: usize) {
+        let spawn = self.scenario.spawn;
+        let mut dependencies = BTreeSet::new();
+        if spawn.dependency_chance > 0. && self.rng.gen_bool(spawn.dependency_chance) {
+            let open = self
+                .work_items
+                .keys()
+                .copied()
+                .filter(|other| *other != uuid)
+                .collect::<Vec<_>>();
+            if let Some(prerequisite) = open.choose(&mut self.rng) {
+                dependencies.insert(Dependency::WorkItem(*prerequisite));
+            }
+        }
+        if spawn.other_team_chance > 0. && self.rng.gen_bool(spawn.other_team_chance) {
+            let days = self.rng.gen_range(OTHER_TEAM_DAYS.clone());
+            dependencies.insert(Dependency::OtherTeam {
+                ready_at: self.clock.ticks() + days * self.clock.ticks_per_day(),
+            });
+        }
+
+        if let Some(work_item) = self.work_items.get_mut(&uuid) {
+            work_item.dependencies = dependencies;
+        }
+    }
+
+    /// Dependencies of a work item that aren't done yet, it is blocked for as long as there are any
+    pub fn blockers(&self, uuid: usize) -> Vec<Dependency> {
+        let Some(work_item) = self.work_items.get(&uuid) else {
+            return vec![];
+        };
+
+        work_item
+            .dependencies
+            .iter()
+            .copied()
+            .filter(|dependency| match dependency {
+                Dependency::WorkItem(prerequisite) => self.work_items.contains_key(prerequisite),
+                Dependency::OtherTeam {
+                    ready_at,
+                } => *ready_at > self.clock.ticks(),
+            })
+            .collect()
+    }
+
+    pub fn is_blocked(&self, uuid: usize) -> bool {
+        !self.blockers(uuid).is_empty()
+    }
+
+    /// What holds up a work item, following its first blocker to whatever blocks that one in turn.
+    /// Work items only depend on older ones, so the chain always ends.
+    pub fn blocking_chain(&self, uuid: usize) -> Vec<Dependency> {
+        let mut chain = vec![];
+        let mut current = uuid;
+        while let Some(blocker) = self.blockers(current).first().copied() {
+            chain.push(blocker);
+            match blocker {
+                Dependency::WorkItem(prerequisite) => current = prerequisite,
+                Dependency::OtherTeam {
+                    ..
+                } => break,
+            }
+        }
+
+        chain
+    }
+
+    /// Puts a bug fix on the board for every escaped defect noticed by now, as long as the backlog
+    /// has room for it.
+    fn surface_defects(&mut self) {
+        let now = self.clock.ticks();
+        let mut index = 0;
+        while index < self.escaped_defects.len() {
+            let defect = self.escaped_defects[index];
+            if defect.surfaces_at > now || !self.has_room(Column::Backlog, None) {
+                index += 1;
+                continue;
+            }
+
+            self.escaped_defects.remove(index);
+            self.add_work_item(WorkItemKind::BugFix, Some(defect.origin));
+            self.defects.surfaced += 1;
+        }
+    }
+
+    /// Latest known state of an employee, whether they are still on the team or not
+    fn employee(&self, employee_id: EmployeeId) -> Option<&EmployeeActor> {
+        self.employee_data.get(&employee_id).or_else(|| {
+            self.former_employees
+                .get(&employee_id)
+                .map(|(_, employee)| employee)
+        })
+    }
 
-        // Title row
-        queue!(
-            self.stdout,
-            style::Print("Tasks"),
-            cursor::MoveToNextLine(1)
+    /// Rolls the defects slipping into the change written in a finished `CreatePR`, and the ones
+    /// spotted in a finished review or test. Returns how many were spotted.
+    fn check_defects(
+        &mut self,
+        uuid: usize,
+        task: &Task,
+        contributors: &BTreeSet<EmployeeId>,
+    ) -> u32 {
+        let team = contributors
+            .iter()
+            .filter_map(|employee_id| self.employee(*employee_id))
+            .collect::<Vec<_>>();
+        if team.is_empty() {
+            return 0;
+        }
+        // Authors share the blame, the most thorough reviewer is the one who finds things. Smaller
+        // changes such as rework have fewer places for defects to hide in.
+        let defect_chance = if task.id == TaskId::CreatePR {
+            let size = task.total_energy_required
+                / self
+                    .sized(uuid, self.scenario.task(TaskId::CreatePR))
+                    .total_energy_required;
+            team.iter()
+                .map(|author| author.defect_chance())
+                .sum::<f64>()
+                / team.len() as f64
+                * size.min(1.) as f64
+        } else {
+            0.
+        };
+        let catch_chance = if checks_for_defects(task.id) {
+            team.iter()
+                .map(|reviewer| reviewer.catch_chance())
+                .fold(0., f64::max)
+        } else {
+            0.
+        };
+
+        let introduced = (0..MAX_DEFECTS_PER_CHANGE)
+            .filter(|_| defect_chance > 0. && self.rng.gen_bool(defect_chance))
+            .count() as u32;
+
+        let Some(work_item) = self.work_items.get_mut(&uuid) else {
+            return 0;
+        };
+        work_item.latent_defects += introduced;
+        self.defects.introduced += introduced;
+        let caught = (0..work_item.latent_defects)
+            .filter(|_| catch_chance > 0. && self.rng.gen_bool(catch_chance))
+            .count() as u32;
+        work_item.latent_defects -= caught;
+        self.defects.caught += caught;
+
+        if introduced > 0 {
+            self.emit(EventKind::DefectsIntroduced {
+                uuid,
+                count: introduced,
+                authors: contributors.clone(),
+            });
+        }
+        if caught > 0 {
+            self.emit(EventKind::DefectsCaught {
+                uuid,
+                task: task.id,
+                count: caught,
+                reviewers: contributors.clone(),
+            });
+        }
+
+        caught
+    }
+
+    /// Verdict of the reviewers in `contributors` on the change of a work item
+    fn review(
+        &mut self,
+        uuid: usize,
+        contributors: &BTreeSet<EmployeeId>,
+        defects_found: u32,
+    ) -> ReviewOutcome {
+        let Some(work_item) = self.work_items.get_mut(&uuid) else {
+            return ReviewOutcome::Approved;
+        };
+        work_item.review_rounds += 1;
+        let authors = work_item.authors();
+
+        let team = |employee_ids: &BTreeSet<EmployeeId>| {
+            employee_ids
+                .iter()
+                .filter_map(|employee_id| self.employee(*employee_id))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let authors = team(&authors);
+        let reviewers = team(contributors);
+
+        review_outcome(
+            &authors.iter().collect::<Vec<_>>(),
+            &reviewers.iter().collect::<Vec<_>>(),
+            defects_found,
+            &mut self.rng,
         )
-        .unwrap();
-
-        let capped_list = &sorted_task_list[0..6.min(sorted_task_list.len())];
-        for (_id, (task, contributors)) in capped_list.iter() {
-            // Start row
-            queue!(self.stdout, cursor::MoveToNextLine(1)).unwrap();
-            let print = if task.id == TaskId::CoffeeBreak {
-                style::Print(format!(
-                    "{0: <23}",
-                    format!("䷢ [{:<11}]         ", format!("{:?}", task.id))
-                ))
-            } else {
-                style::Print(format!(
-                    "{0: <23}",
-                    format!("䷢ [{:<11}] {} ", format!("{:?}", task.id), task.name)
-                ))
-            };
+    }
 
-            // Title
-            queue!(
-                self.stdout,
-                print
-            )
-            .unwrap();
-
-            // Progress bar + percentage
-            draw_task_progress(
-                &mut self.stdout,
-                progress_color,
-                task.progress(),
-                max_bar_width,
-            );
-
-            draw_contributors(&mut self.stdout, contributors);
-        }
-        if !self.done_list.is_empty() {
-            queue!(
-                self.stdout,
-                cursor::MoveToNextLine(1),
-                cursor::MoveToNextLine(1),
-                style::Print("Done"),
-                cursor::MoveToNextLine(1)
+    /// Gives the change of a work item back to its authors after a review that didn't approve
+    /// it, as a smaller rework task or, when rejected, to be written again from scratch. Returns
+    /// `false` when its workflow has no `CreatePR` stage to go back to.
+    fn send_back(&mut self, uuid: usize, task: &Task, outcome: ReviewOutcome) -> bool {
+        let Some(stage) = self
+            .scenario
+            .workflows
+            .stages(task.kind)
+            .iter()
+            .position(|id| *id == TaskId::CreatePR)
+        else {
+            return false;
+        };
+
+        let mut rework = Task {
+            priority: task.priority,
+            rework: true,
+            ..self.sized(
+                uuid,
+                self.scenario
+                    .task(TaskId::CreatePR)
+                    .as_stage(task.kind, stage),
             )
-            .unwrap();
+        };
+        match outcome {
+            ReviewOutcome::ChangesRequested => {
+                rework.total_energy_required *= REWORK_SIZE;
+                rework.estimated_energy *= REWORK_SIZE;
+            }
+            // Whatever was wrong with the change goes away with it
+            _ => {
+                if let Some(work_item) = self.work_items.get_mut(&uuid) {
+                    work_item.latent_defects = 0;
+                }
+            }
         }
+        // Back to an earlier column whatever its limit, the work item never left the team's hands
+        self.insert_task(uuid, rework);
+
+        true
+    }
+
+    /// Priority the work item with `uuid` gained as its due date got near
+    pub fn escalation(&self, uuid: usize) -> u32 {
+        self.work_items
+            .get(&uuid)
+            .and_then(|work_item| work_item.due_at)
+            .map_or(0, |due_at| {
+                escalation(due_at, self.clock.ticks(), self.clock.ticks_per_day())
+            })
+    }
 
-        // Draw done tasks
-        for (_uuid, task, contributors) in self.done_list.iter() {
-            let print = if task.id == TaskId::CoffeeBreak {
-                style::Print(format!(
-                    "{0: <23}",
-                    format!("✓ [{:<11}]         ", format!("{:?}", task.id))
-                ))
+    /// Uuid of the delivered work item the bug fix with `uuid` is for
+    pub fn fixes(&self, uuid: usize) -> Option<usize> {
+        self.work_items
+            .get(&uuid)
+            .and_then(|work_item| work_item.fixes)
+    }
+
+    pub fn is_overdue(&self, uuid: usize) -> bool {
+        self.work_items
+            .get(&uuid)
+            .is_some_and(|work_item| work_item.is_overdue(self.clock.ticks()))
+    }
+
+    /// Priority of a task from triage and from its due date getting near
+    fn priority(&self, uuid: usize, task: &Task) -> u32 {
+        task.priority + self.escalation(uuid)
+    }
+
+    pub fn sorted_task_list(&self) -> Vec<TaskEntry> {
+        let mut task_list = self
+            .task_list
+            .iter()
+            .map(|(uuid, val)| (*uuid, val.clone()))
+            .collect::<Vec<TaskEntry>>();
+        task_list.sort_by(|a, b| {
+            let task_a: Task = a.1 .0;
+            let task_b: Task = b.1 .0;
+            let priority_a = self.priority(a.0, &task_a);
+            let priority_b = self.priority(b.0, &task_b);
+
+            if task_b.id.priority() != task_a.id.priority() {
+                task_b.id.priority().cmp(&task_a.id.priority())
+            } else if priority_b != priority_a {
+                priority_b.cmp(&priority_a)
             } else {
-                style::Print(format!(
-                    "{0: <23}",
-                    format!("✓ [{:<11}] {} ", format!("{:?}", task.id), task.name)
-                ))
-            };
+                task_b
+                    .estimated_progress()
+                    .partial_cmp(&task_a.estimated_progress())
+                    .unwrap()
+            }
+        });
 
-            queue!(
-                self.stdout,
-                cursor::MoveToNextLine(1),
-                print
-            )
-            .unwrap();
-            draw_task_progress(&mut self.stdout, done_color, 1.0, max_bar_width);
+        task_list
+    }
 
-            draw_contributors(&mut self.stdout, contributors);
+    pub fn wip_limits(&self) -> &WipLimits {
+        &self.scenario.wip_limits
+    }
+
+    /// How many work items are in `column`, leaving out the one with uuid `except`
+    pub fn column_count(&self, column: Column, except: Option<usize>) -> usize {
+        if column == Column::Done {
+            return self.delivered.len();
         }
 
-        // Flush last
-        self.stdout.flush().unwrap();
+        self.task_list
+            .iter()
+            .filter(|(uuid, (task, _))| Some(**uuid) != except && Column::of(task) == Some(column))
+            .count()
     }
-}
 
-impl Drop for Kanban {
-    fn drop(&mut self) {
-        queue!(self.stdout, terminal::LeaveAlternateScreen).unwrap();
-        self.stdout.flush().unwrap();
+    fn has_room(&self, column: Column, except: Option<usize>) -> bool {
+        self.wip_limits()
+            .limit(column)
+            .is_none_or(|limit| self.column_count(column, except) < limit)
     }
-}
 
-impl Actor for Kanban {
-    type Context = Context<Self>;
+    /// Open tasks the scheduler can hand out, highest priority first. Finished stages waiting for
+    /// room and blocked work items are left out, and so are the backlog items that would go over
+    /// the limit of their column once started.
+    fn assignable_tasks(&self) -> Vec<TaskEntry> {
+        let mut started = BTreeMap::<Column, usize>::new();
 
-    fn started(&mut self, ctx: &mut Self::Context) {
-        ctx.set_mailbox_capacity(10);
+        self.sorted_task_list()
+            .into_iter()
+            .filter(|(uuid, (task, _))| {
+                if task.is_done() || task.impeded || self.is_blocked(*uuid) {
+                    return false;
+                }
+                if Column::of(task) != Some(Column::Backlog) {
+                    return true;
+                }
+                let Some(column) = Column::of_stage(task.id) else {
+                    return true;
+                };
+
+                let count = self.column_count(column, None) + started.get(&column).unwrap_or(&0);
+                let has_room = self
+                    .wip_limits()
+                    .limit(column)
+                    .is_none_or(|limit| count < limit);
+                if has_room {
+                    *started.entry(column).or_default() += 1;
+                }
+                has_room
+            })
+            .collect()
+    }
+
+    /// Moves the finished stages that wait for room on to their next stage, highest priority first.
+    fn move_waiting_work_items(&mut self) {
+        let waiting = self
+            .sorted_task_list()
+            .into_iter()
+            .filter(|(_, (task, _))| task.is_done())
+            .collect::<Vec<_>>();
+        for (uuid, (task, _)) in waiting {
+            self.move_on(uuid, &task);
+        }
+    }
 
-        ctx.run_interval(
-            Duration::from_secs_f32(1. / TICK_RATE),
-            |kanban, context| kanban.tick(context),
+    /// Hires and fires the people the scenario plans for by today. They are handled right away
+    /// rather than sent, so that they happen at the same point of the tick on every run.
+    fn apply_staffing(&mut self, context: &mut Context<Kanban>) {
+        while let Some(event) = self.scenario.staffing.get(self.next_staffing) {
+            if event.day > self.clock.days() {
+                break;
+            }
+            let event = event.clone();
+            self.next_staffing += 1;
+
+            if let Some(employee) = event.hire {
+                self.handle(
+                    Hire {
+                        employee,
+                    },
+                    context,
+                );
+            }
+            // Scenarios fire by name, it is unique on the team by the time the event comes
+            let employee_id = event.fire.and_then(|name| {
+                self.employee_data
+                    .values()
+                    .find(|employee| employee.employee_name == name)
+                    .map(|employee| employee.employee_id)
+            });
+            if let Some(employee_id) = employee_id {
+                self.handle(
+                    Fire {
+                        employee_id,
+                    },
+                    context,
+                );
+            }
+        }
+    }
+
+    fn add_employee(&mut self, add_employee: AddEmployee) {
+        let employee_id = add_employee.employee_data.employee_id;
+        let avatar = add_employee
+            .avatar
+            .unwrap_or_else(|| avatar::generate(&add_employee.employee_data.employee_name));
+        self.avatars.insert(employee_id, avatar);
+        self.record_skills(&add_employee.employee_data);
+        self.emit(EventKind::EmployeeJoined {
+            employee: employee_id,
+            name: add_employee.employee_data.employee_name.clone(),
+        });
+        self.employee_addresses
+            .insert(employee_id, add_employee.employee_address);
+        self.employee_data
+            .insert(employee_id, add_employee.employee_data);
+    }
+
+    /// Brings a new employee onto the team. Unless the scenario says otherwise they know little
+    /// about the company, and they spend their first days onboarding with a mentor.
+    fn hire(&mut self, employee: EmployeeSpec) {
+        let characteristics = employee.characteristics.unwrap_or_else(|| {
+            let characteristics = EmployeeCharacteristics::new(&mut self.rng);
+            EmployeeCharacteristics {
+                company_experience: self.rng.gen_range(0.0..NEW_HIRE_EXPERIENCE),
+                ..characteristics
+            }
+        });
+        let employee_id = self.next_employee_id;
+        self.next_employee_id = EmployeeId(employee_id.0 + 1);
+        let mut employee_data = EmployeeActor::new(
+            employee_id,
+            employee.employee_type,
+            self.scenario.role(employee.employee_type),
+            employee.name,
+            characteristics,
+            employee.resources,
+            self.rng.gen(),
         );
+        employee_data.onboarding_days = ONBOARDING_DAYS;
+
+        self.add_employee(AddEmployee {
+            employee_address: Employee::new(employee_data.clone()).addr,
+            employee_data,
+            avatar: employee.avatar_art,
+        });
+        self.assign_mentor(employee_id);
     }
-}
 
-impl Handler<Task> for Kanban {
-    type Result = ();
+    /// Pairs a new hire with the most experienced colleague who isn't onboarding themselves.
+    fn assign_mentor(&mut self, employee_id: EmployeeId) {
+        let Some(newcomer) = self.employee_data.get(&employee_id) else {
+            return;
+        };
+        if !newcomer.is_onboarding() || !newcomer.role.takes_tasks() {
+            return;
+        }
 
-    fn handle(&mut self, task: Task, _ctx: &mut Context<Self>) -> Self::Result {
-        self.task_list
-            .insert(create_task_id(), (task, BTreeSet::new()));
+        let mentor = self
+            .employee_data
+            .values()
+            .filter(|employee| {
+                employee.employee_id != employee_id
+                    && employee.role.takes_tasks()
+                    && !employee.is_onboarding()
+            })
+            .max_by(|a, b| {
+                a.characteristics
+                    .company_experience
+                    .total_cmp(&b.characteristics.company_experience)
+            })
+            .map(|mentor| mentor.employee_id);
+        match mentor {
+            Some(mentor) => {
+                self.mentors.insert(employee_id, mentor);
+            }
+            None => {
+                self.mentors.remove(&employee_id);
+            }
+        }
     }
-}
 
-impl Handler<WorkCompleted> for Kanban {
-    type Result = ();
+    /// Lets an employee go. The unfinished tasks they worked on go back to the board for anyone
+    /// to pick up, and the new hires they looked after get another mentor.
+    fn fire(&mut self, employee_id: EmployeeId) {
+        self.employee_addresses.remove(&employee_id);
+        let Some(employee_data) = self.employee_data.remove(&employee_id) else {
+            return;
+        };
+        self.former_employees
+            .insert(employee_id, (self.clock.days(), employee_data));
+        self.emit(EventKind::EmployeeLeft {
+            employee: employee_id,
+        });
 
-    fn handle(&mut self, work_completed: WorkCompleted, ctx: &mut Context<Self>) -> Self::Result {
-        if let Some((task, contributors)) = self.task_list.get_mut(&work_completed.uuid) {
-            task.energy_taken += work_completed.energy_add;
-            task.energy_taken = task.energy_taken.clamp(0.0, task.total_energy_required);
-            contributors.insert(work_completed.employee_name.to_string());
-
-            if task.is_done() {
-                if let Some((task, contributors)) = self.task_list.remove(&work_completed.uuid) {
-                    match task.id {
-                        TaskId::CreatePR => ctx.notify(TaskId::ReviewPR.to_task()),
-                        TaskId::ReviewPR => ctx.notify(TaskId::MergePR.to_task()),
-                        TaskId::CoffeeBreak => {
-                            if contributors.len() > 1 {
-                                self.employee_addresses.iter().for_each(|addr| {
-                                    addr.do_send(Buff {
-                                        id: BuffId::Caffeinated,
-                                    })
-                                })
-                            } else {
-                                work_completed.employee_address.do_send(Buff {
-                                    id: BuffId::Caffeinated,
-                                });
-                            }
-                        }
-                        _ => {}
-                    }
-                    self.done_list
-                        .push_front((work_completed.uuid, task, contributors));
-                    if self.done_list.len() > 5 {
-                        self.done_list.pop_back();
-                    }
-                }
+        for (task, contributors) in self.task_list.values_mut() {
+            if !task.is_done() {
+                contributors.remove(&employee_id);
             }
         }
+
+        self.mentors.remove(&employee_id);
+        let mentored = self
+            .mentors
+            .iter()
+            .filter(|(_, mentor)| **mentor == employee_id)
+            .map(|(newcomer, _)| *newcomer)
+            .collect::<Vec<_>>();
+        for newcomer in mentored {
+            self.assign_mentor(newcomer);
+        }
     }
-}
 
-pub struct AddEmployee {
-    pub employee_address: Addr<EmployeeActor>,
-}
+    /// Keeps the latest state an employee reported, along with the effects that came over them
+    /// since the last one
+    fn update_employee(&mut self, mut employee_data: EmployeeActor) {
+        let employee_id = employee_data.employee_id;
+        for effect in std::mem::take(&mut employee_data.applied_effects) {
+            self.emit(EventKind::BuffApplied {
+                employee: employee_id,
+                buff: effect.id,
+                stacks: effect.stacks,
+                until_tick: effect.until_tick,
+            });
+        }
+        self.emit(EventKind::EmployeeUpdated {
+            employee: employee_id,
+            characteristics: employee_data.characteristics,
+            resources: employee_data.resources,
+            sick_days: employee_data.sick_days,
+        });
 
-impl Message for AddEmployee {
-    type Result = ();
-}
+        self.employee_data.insert(employee_id, employee_data);
+    }
 
-impl Handler<AddEmployee> for Kanban {
-    type Result = ();
+    /// Name of an employee, whether they are still on the team or not
+    pub fn employee_name(&self, employee_id: EmployeeId) -> &str {
+        self.employee(employee_id)
+            .map_or("?", |employee| employee.employee_name.as_str())
+    }
 
-    fn handle(&mut self, add_employee: AddEmployee, _ctx: &mut Context<Self>) -> Self::Result {
-        self.employee_addresses.push(add_employee.employee_address);
+    /// Keeps the characteristics of `employee` for the current day, unless it already has them
+    fn record_skills(&mut self, employee: &EmployeeActor) {
+        let day = self.clock.days();
+        let history = self.skill_history.entry(employee.employee_id).or_default();
+        if history.last().is_none_or(|sample| sample.day < day) {
+            history.push(SkillSample {
+                day,
+                characteristics: employee.characteristics,
+            });
+        }
     }
-}
 
-impl Handler<EmployeeActor> for Kanban {
-    type Result = ();
+    /// Everyone at work but the managers, by id
+    fn developers(&self) -> impl Iterator<Item = &EmployeeActor> {
+        self.employee_data.values().filter(|employee| {
+            employee.employee_type != EmployeeType::Manager && employee.sick_days == 0
+        })
+    }
 
-    fn handle(&mut self, employee_data: EmployeeActor, _ctx: &mut Context<Self>) -> Self::Result {
-        self.employee_data
-            .insert(employee_data.employee_name.to_string(), employee_data);
+    fn send_stress(&self, employee_ids: &BTreeSet<EmployeeId>, event: StressEvent) {
+        for employee_id in employee_ids {
+            if let Some(address) = self.employee_addresses.get(employee_id) {
+                address.do_send(Stress {
+                    event,
+                });
+            }
+        }
     }
-}
 
-fn draw_task_progress<W>(w: &mut W, color: Color, progress: f32, max_width: u16)
-where
-    W: Write,
-{
-    let limit = (progress * max_width as f32).round() as u16;
-    for col in 0..=max_width {
-        if col < limit || progress == 1.0 {
-            queue!(w, style::SetForegroundColor(color), style::Print("███")).unwrap();
-        } else {
-            queue!(
-                w,
-                style::SetForegroundColor(Color::Black),
-                style::Print("███")
-            )
-            .unwrap();
+    /// Holds up a started task now and then, and lets impediments go away on their own.
+    fn update_impediments(&mut self) {
+        let mut faded = vec![];
+        for (uuid, (task, _)) in self.task_list.iter_mut() {
+            if task.impeded && self.rng.gen_bool(IMPEDIMENT_FADE_CHANCE) {
+                task.impeded = false;
+                faded.push((*uuid, task.id));
+            }
+        }
+        for (uuid, task) in faded {
+            self.emit(EventKind::ImpedimentCleared {
+                uuid,
+                task,
+                manager: None,
+            });
+        }
+
+        if !self.rng.gen_bool(self.scenario.spawn.impediment_chance) {
+            return;
+        }
+        let started = self
+            .task_list
+            .iter()
+            .filter(|(_, (task, _))| {
+                !task.is_done()
+                    && !task.impeded
+                    && !matches!(Column::of(task), None | Some(Column::Backlog))
+            })
+            .map(|(uuid, _)| *uuid)
+            .collect::<Vec<_>>();
+        if let Some(uuid) = started.choose(&mut self.rng) {
+            if let Some((task, contributors)) = self.task_list.get_mut(uuid) {
+                task.impeded = true;
+                let task = task.id;
+                let contributors = contributors.clone();
+                self.send_stress(&contributors, StressEvent::Incident);
+                self.emit(EventKind::ImpedimentRaised {
+                    uuid: *uuid,
+                    task,
+                });
+            }
         }
     }
-    queue!(
-        w,
-        style::SetForegroundColor(Color::White),
-        style::Print(format!(" {:.2} % ", progress * 100.0)),
-    )
-    .unwrap();
-}
 
-fn draw_contributors<W>(w: &mut W, contributors: &BTreeSet<String>)
-where
-    W: Write,
-{
-    let mut count = 0;
-    for c in contributors.iter() {
-        queue!(w, style::Print(format!("{c}"))).unwrap();
-        count += 1;
-        if count < contributors.len() {
-            queue!(w, style::Print(", ")).unwrap();
+    /// The state of the board as managers see it, blocked work items are no use triaging
+    fn manage(&self) -> Manage {
+        let backlog = self
+            .task_list
+            .iter()
+            .filter(|(uuid, (task, _))| {
+                Column::of(task) == Some(Column::Backlog) && !self.is_blocked(**uuid)
+            })
+            .filter_map(|(uuid, (task, _))| {
+                let work_item = self.work_items.get(uuid)?;
+                Some(BacklogItem {
+                    uuid: *uuid,
+                    kind: task.kind,
+                    waiting: self.clock.ticks() - work_item.created_at,
+                    priority: self.priority(*uuid, task),
+                })
+            })
+            .collect();
+        let impeded = self
+            .sorted_task_list()
+            .into_iter()
+            .filter(|(_, (task, _))| task.impeded)
+            .map(|(uuid, _)| uuid)
+            .collect();
+        let stress = self
+            .developers()
+            .map(|employee| employee.resources.stress)
+            .collect::<Vec<_>>();
+        let team_stress = stress.iter().sum::<f32>() / stress.len().max(1) as f32;
+
+        Manage {
+            backlog,
+            impeded,
+            team_stress,
         }
     }
-}
 
-fn draw_employee_card<W>(w: &mut W, employee: &EmployeeActor, employee_tasks: &Vec<Task>, okko: &str, anton: &str)
-where
-    W: Write,
-{
-    let card_height = 20;
-    let card_width = 90;
+    fn complete_management(&mut self, management_done: ManagementDone) {
+        let manager = management_done.employee_data.employee_id;
+        self.update_employee(management_done.employee_data);
+        if let Some(action) = management_done.action {
+            self.emit(EventKind::ManagerActed {
+                manager,
+                action,
+            });
+        }
 
-    for y in 0..card_height {
-        for x in 0..card_width {
-            if (y == 0 || y == card_height - 1) || (x == 0 || x == card_width - 1) {
-                queue!(
-                    w,
-                    cursor::MoveTo(x, y),
-                    style::PrintStyledContent("█".underlined().green())
-                )
-                .unwrap();
+        match management_done.action {
+            Some(ManagerAction::Triage {
+                uuid,
+            }) => {
+                if let Some((task, _)) = self.task_list.get_mut(&uuid) {
+                    task.priority += 1;
+                }
+            }
+            Some(ManagerAction::Unblock {
+                uuid,
+            }) => {
+                let cleared = self
+                    .task_list
+                    .get_mut(&uuid)
+                    .filter(|(task, _)| task.impeded)
+                    .map(|(task, _)| {
+                        task.impeded = false;
+                        task.id
+                    });
+                if let Some(task) = cleared {
+                    self.emit(EventKind::ImpedimentCleared {
+                        uuid,
+                        task,
+                        manager: Some(manager),
+                    });
+                }
             }
+            Some(ManagerAction::Meeting {
+                focus_cost,
+                stress_relief,
+            }) => {
+                for developer in self.developers() {
+                    if let Some(address) = self.employee_addresses.get(&developer.employee_id) {
+                        address.do_send(Meeting {
+                            focus_cost,
+                            stress_relief,
+                        });
+                    }
+                }
+            }
+            None => {}
         }
     }
 
-    let name_file = if employee.employee_name == "Okko" {
-        "okko.txt".to_string()
-    } else {
-        "anton.txt".to_string()
-    };
+    fn complete_work(&mut self, work_completed: WorkCompleted) {
+        for task_id in work_completed.spawned_tasks {
+            self.add_task(self.scenario.task(task_id));
+        }
+        self.update_employee(work_completed.employee_data);
+
+        let uuid = work_completed.uuid;
+        let Some((task, contributors)) = self.task_list.get_mut(&uuid) else {
+            return;
+        };
+        task.energy_taken += work_completed.energy_add;
+        task.energy_taken = task.energy_taken.clamp(0.0, task.total_energy_required);
+        contributors.insert(work_completed.employee_id);
+
+        // Going past the estimate makes the team look at the task again, and see how much of it is
+        // really left
+        if !task.is_done() && task.energy_taken >= task.estimated_energy {
+            task.estimated_energy = task.total_energy_required;
+            if let Some(work_item) = self.work_items.get_mut(&uuid) {
+                work_item.surprises += 1;
+            }
+        }
+        let task = *task;
+        self.emit(EventKind::TaskProgressed {
+            uuid,
+            task: task.id,
+            employee: work_completed.employee_id,
+            energy: work_completed.energy_add,
+            energy_taken: task.energy_taken,
+            energy_required: task.total_energy_required,
+        });
 
-    queue!(w, cursor::MoveTo(1, 1),).unwrap();
+        if !task.is_done() {
+            return;
+        }
+        let Some((task, contributors)) = self.task_list.remove(&uuid) else {
+            return;
+        };
+        *self.completed_tasks.entry(task.id).or_default() += 1;
+        self.emit(EventKind::TaskCompleted {
+            uuid,
+            task: task.id,
+            contributors: contributors.clone(),
+        });
+        match task.id {
+            TaskId::CoffeeBreak => {
+                self.send_stress(&contributors, StressEvent::Break);
+                // A coffee break taken together gets the whole team going
+                let caffeinated = if contributors.len() > 1 {
+                    self.employee_addresses.keys().copied().collect()
+                } else {
+                    vec![work_completed.employee_id]
+                };
+                self.buffs.extend(
+                    caffeinated
+                        .into_iter()
+                        .map(|employee_id| (employee_id, BuffId::Caffeinated)),
+                );
+            }
+            _ => {
+                self.send_stress(&contributors, StressEvent::FinishedWork);
+                self.finish_stage(uuid, &task, &contributors);
+            }
+        }
+        self.done_list.push_front((uuid, task, contributors));
+        if self.done_list.len() > 5 {
+            self.done_list.pop_back();
+        }
+    }
 
-    draw_file_name(w, name_file, okko, anton);
+    /// Records who worked on the stage and what it did to the defects of the work item, and moves
+    /// the work item on. Reviews that don't approve the change send it back to its authors instead.
+    fn finish_stage(&mut self, uuid: usize, task: &Task, contributors: &BTreeSet<EmployeeId>) {
+        let Some(work_item) = self.work_items.get_mut(&uuid) else {
+            return;
+        };
+        work_item.stages.push((task.id, contributors.clone()));
+        let defects_found = self.check_defects(uuid, task, contributors);
+
+        if task.id == TaskId::ReviewPR {
+            let outcome = self.review(uuid, contributors, defects_found);
+            self.reviews.count(outcome);
+            let (authors, round) = self
+                .work_items
+                .get(&uuid)
+                .map(|work_item| (work_item.authors(), work_item.review_rounds))
+                .unwrap_or_default();
+            self.emit(EventKind::ReviewFinished {
+                uuid,
+                outcome,
+                reviewers: contributors.clone(),
+                round,
+            });
+            match outcome {
+                ReviewOutcome::Approved => {}
+                ReviewOutcome::ChangesRequested => {
+                    self.send_stress(&authors, StressEvent::ChangesRequested)
+                }
+                ReviewOutcome::Rejected => self.send_stress(&authors, StressEvent::Rejected),
+            }
+            if outcome != ReviewOutcome::Approved && self.send_back(uuid, task, outcome) {
+                return;
+            }
+        }
 
-    draw_current_tasks(w, employee_tasks);
+        self.move_on(uuid, task);
+    }
 
-    draw_characteristics(w, employee);
+    /// Puts the next stage of a work item on the board under the same uuid, or moves it to the
+    /// delivered ones when `task` was its last stage. When the column of the next stage is full the
+    /// finished `task` stays on the board to wait for room.
+    fn move_on(&mut self, uuid: usize, task: &Task) {
+        match self.scenario.next_stage(task) {
+            Some(next_task) => {
+                let next_task = self.sized(uuid, next_task);
+                let column = Column::of(&next_task).expect("Workflow stages have a column");
+                if self.has_room(column, Some(uuid)) {
+                    self.insert_task(uuid, next_task);
+                } else if let Entry::Vacant(entry) = self.task_list.entry(uuid) {
+                    entry.insert((*task, BTreeSet::new()));
+                    self.emit(EventKind::WorkItemWaiting {
+                        uuid,
+                        task: task.id,
+                        column,
+                    });
+                }
+            }
+            None => {
+                self.task_list.remove(&uuid);
+                if let Some(mut work_item) = self.work_items.remove(&uuid) {
+                    // The clock only moves once the tick is over
+                    work_item.delivered_at = Some(self.clock.ticks() + 1);
+                    for _ in 0..work_item.latent_defects {
+                        let days = self.rng.gen_range(SURFACE_DAYS.clone());
+                        self.escaped_defects.push(EscapedDefect {
+                            origin: uuid,
+                            surfaces_at: self.clock.ticks() + days * self.clock.ticks_per_day(),
+                        });
+                    }
+                    self.defects.escaped += work_item.latent_defects;
+                    self.emit(EventKind::WorkItemDelivered {
+                        uuid,
+                        kind: work_item.kind,
+                        lead_time: work_item.lead_time().unwrap_or_default(),
+                    });
+                    if work_item.latent_defects > 0 {
+                        self.emit(EventKind::DefectsEscaped {
+                            uuid,
+                            count: work_item.latent_defects,
+                        });
+                    }
+                    self.delivered.push(work_item);
+                }
+            }
+        }
+    }
+}
 
-    draw_resources(w, employee);
+impl Actor for Kanban {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.set_mailbox_capacity(10);
 
-    queue!(w, cursor::MoveTo(0, card_height + 1),).unwrap();
+        if self.tui.is_some() {
+            ctx.run_interval(Duration::from_millis(20), |kanban, context| {
+                kanban.handle_keys(context)
+            });
+        }
+        self.schedule_tick(ctx);
+    }
 }
 
-fn draw_characteristics<W>(w: &mut W, employee: &EmployeeActor)
-where
-    W: Write,
-{
-    let section_start = (69, 2);
-
-    queue!(
-        w,
-        cursor::MoveTo(section_start.0, section_start.1),
-        style::PrintStyledContent("Characteristics".underlined().red()),
-        cursor::MoveTo(section_start.0, section_start.1 + 2),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Rigor: {:.0}", employee.characteristics.rigor)
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 3),
-        style::Print(&format!(
-            "{0: <20}",
-            format!(
-                "Experience: {:.0}",
-                employee.characteristics.company_experience
-            )
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 4),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Skills: {:.0}", employee.characteristics.programming_skills)
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 5),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Fitness: {:.0}", employee.characteristics.fitness)
-        )),
-    )
-    .unwrap();
+impl Handler<Task> for Kanban {
+    type Result = ();
+
+    fn handle(&mut self, task: Task, _ctx: &mut Context<Self>) -> Self::Result {
+        self.add_task(task);
+    }
 }
 
-fn draw_resources<W>(w: &mut W, employee: &EmployeeActor)
-where
-    W: Write,
-{
-    let section_start = (69, 9);
-
-    queue!(
-        w,
-        cursor::MoveTo(section_start.0, section_start.1),
-        style::PrintStyledContent("Resources".underlined().red()),
-        cursor::MoveTo(section_start.0, section_start.1 + 2),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Energy: {:.0}", employee.resources.energy)
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 3),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Focus: {:.0}", employee.resources.focus)
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 4),
-        style::Print(&format!(
-            "{0: <20}",
-            format!("Stress: {:.0}", employee.resources.stress)
-        )),
-        cursor::MoveTo(section_start.0, section_start.1 + 5),
-    )
-    .unwrap();
+pub struct AddEmployee {
+    pub employee_address: Addr<EmployeeActor>,
+    /// State of the employee when they join
+    pub employee_data: EmployeeActor,
+    /// ASCII art of the employee, generated from their name when `None`
+    pub avatar: Option<String>,
 }
 
-fn draw_current_tasks<W>(w: &mut W, employee_tasks: &Vec<Task>)
-where
-    W: Write,
-{
-    let section_start = (5, 10);
-
-    queue!(
-        w,
-        cursor::MoveTo(section_start.0, section_start.1),
-        style::PrintStyledContent("Ongoing tasks".underlined().red()),
-    )
-    .unwrap();
-    let capped_tasks = &employee_tasks[0..5.min(employee_tasks.len())];
-    for (i, task) in capped_tasks.iter().enumerate() {
-        queue!(
-            w,
-            cursor::MoveTo(section_start.0, section_start.1 + 2 + i as u16),
-            style::PrintStyledContent(format!("[{:?}] ", task.id).green()),
-            style::PrintStyledContent(format!("{0: <10}", task.name).white()),
-        )
-        .unwrap();
-        draw_task_progress(w, Color::Green, task.progress(), 10);
+impl Message for AddEmployee {
+    type Result = ();
+}
+
+impl Handler<AddEmployee> for Kanban {
+    type Result = ();
+
+    fn handle(&mut self, add_employee: AddEmployee, _ctx: &mut Context<Self>) -> Self::Result {
+        self.add_employee(add_employee);
     }
 }
 
-fn draw_file_name<W>(w: &mut W, file_name: String, okko: &str, anton: &str)
-where
-    W: Write,
-{
-    let data_name = if file_name == "okko.txt" { okko } else { anton };
+/// Brings someone new onto the team in the middle of a run
+pub struct Hire {
+    pub employee: EmployeeSpec,
+}
 
-    let data = data_name.split("\n");
+impl Message for Hire {
+    type Result = ();
+}
 
-    queue!(w, cursor::MoveRight(4), cursor::MoveDown(1)).unwrap();
+impl Handler<Hire> for Kanban {
+    type Result = ();
 
-    for d in data {
-        queue!(
-            w,
-            style::PrintStyledContent(d.to_string().red()),
-            cursor::MoveToNextLine(1),
-            cursor::MoveRight(5)
-        )
-        .unwrap();
+    fn handle(&mut self, hire: Hire, _ctx: &mut Context<Self>) -> Self::Result {
+        self.hire(hire.employee);
     }
 }
 
-fn draw_time_bar<W>(w: &mut W, start_time: DateTime<Utc>)
-where
-    W: Write,
-{
-    let time_seconds = (Utc::now() - start_time).num_seconds() as f32 * TICK_RATE * 10.0;
-    let time_in_imaginary_hours = time_seconds / 60.0;
-    let days = (time_in_imaginary_hours / 24.0) as i32;
-    let hours = (time_in_imaginary_hours - (days as f32 * 24.0)).round() as i32;
-    queue!(
-        w,
-        cursor::MoveToNextLine(1),
-        style::PrintStyledContent(
-            format!("Days: {}, Hours: {:.2} hours", days, hours)
-                .underlined()
-                .green()
-        ),
-        cursor::MoveToNextLine(1),
-        cursor::MoveToNextLine(1),
-    )
-    .unwrap();
+/// Takes an employee off the team in the middle of a run
+pub struct Fire {
+    pub employee_id: EmployeeId,
+}
+
+impl Message for Fire {
+    type Result = ();
+}
+
+impl Handler<Fire> for Kanban {
+    type Result = ();
+
+    fn handle(&mut self, fire: Fire, _ctx: &mut Context<Self>) -> Self::Result {
+        self.fire(fire.employee_id);
+    }
 }
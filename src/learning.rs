@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    employee::{EmployeeActor, EmployeeCharacteristics},
+    task::Task,
+};
+
+/// Skill gained for every bit of energy put into a task that only exercises that skill, by someone
+/// starting from scratch
+static LEARNING_RATE: f32 = 0.05;
+
+/// Extra learning when working with someone who knows better, at the largest possible gap
+static PAIRING_BONUS: f32 = 1.;
+
+/// Working days on sick leave after which skills start to slip, and how much they slip every day
+/// after that
+static ABSENCE_GRACE_DAYS: u64 = 3;
+static SKILL_DECAY_PER_DAY: f32 = 0.5;
+
+/// Characteristics of an employee at the start of a day
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillSample {
+    pub day: u64,
+    pub characteristics: EmployeeCharacteristics,
+}
+
+impl EmployeeActor {
+    /// Grows the skills `task` exercises with the work just put into it. The closer to 100 a skill
+    /// is the slower it grows, and a more experienced `pair` on the same task speeds it up.
+    pub fn learn(&mut self, task: &Task, energy_add: f32, pair: Option<EmployeeCharacteristics>) {
+        let weights = task.energy_multipliers;
+        let total_weight = weights.company_experience
+            + weights.rigor
+            + weights.programming_skills
+            + weights.fitness;
+        if total_weight <= 0. || energy_add <= 0. {
+            return;
+        }
+
+        let characteristics = &mut self.characteristics;
+        for (skill, weight, pair_skill) in [
+            (
+                &mut characteristics.programming_skills,
+                weights.programming_skills,
+                pair.map(|pair| pair.programming_skills),
+            ),
+            (
+                &mut characteristics.company_experience,
+                weights.company_experience,
+                pair.map(|pair| pair.company_experience),
+            ),
+        ] {
+            let pairing = pair_skill
+                .map(|pair_skill| 1. + PAIRING_BONUS * ((pair_skill - *skill) / 100.).max(0.))
+                .unwrap_or(1.);
+            let headroom = (1. - *skill / 100.).max(0.);
+            *skill += energy_add * LEARNING_RATE * weight / total_weight * headroom * pairing;
+            *skill = skill.clamp(0., 100.);
+        }
+    }
+
+    /// Counts the working days spent on sick leave, skills slip after a few of them in a row.
+    /// Showing up without getting any work, as managers always do, doesn't count.
+    pub fn track_absence(&mut self) {
+        if self.sick_days > 0 {
+            self.days_away += 1;
+        } else {
+            self.days_away = 0;
+        }
+
+        if self.days_away > ABSENCE_GRACE_DAYS {
+            let skills = &mut self.characteristics.programming_skills;
+            *skills = (*skills - SKILL_DECAY_PER_DAY).max(0.);
+        }
+    }
+}
@@ -1,53 +1,264 @@
+mod avatar;
+mod cli;
+mod clock;
+mod column;
+mod deadline;
+mod defect;
+mod dependency;
+mod effect;
 mod employee;
+mod estimate;
+mod event;
 mod kanban;
+mod learning;
+mod manager;
+mod review;
+mod role;
+mod scenario;
+mod scheduler;
+mod staffing;
+mod stress;
+mod summary;
 mod task;
+mod tui;
+mod workflow;
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{stdout, BufWriter, Write},
+    path::Path,
+    sync::mpsc,
+    thread,
+};
 
 use actix::Actor;
+use clap::Parser;
 use kanban::AddEmployee;
-use task::TaskId;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::{
-    employee::{Employee, EmployeeCharacteristics, EmployeeResources, EmployeeType},
-    kanban::Kanban,
+    cli::{BatchArgs, Cli, Command, ReplayArgs, RunArgs},
+    clock::Pace,
+    employee::{Employee, EmployeeActor, EmployeeCharacteristics, EmployeeId},
+    event::{write_json_lines, EventSubscriber},
+    kanban::{Kanban, RunLength, RunOptions},
+    scenario::Scenario,
+    summary::{RunRecord, Summary},
 };
 
-fn main() {
+/// Runs one simulation to its end and returns its summary, telling `subscribers` about everything
+/// that happens along the way.
+fn simulate(
+    seed: u64,
+    scenario: &Scenario,
+    options: RunOptions,
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+) -> Summary {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (report, summary) = mpsc::channel();
+
     let system = actix::System::new();
 
     system.block_on(async {
-        let kanban_address = Kanban::new().start();
-
-        let employee1 = Employee::new(
-            EmployeeType::Developer,
-            "Okko",
-            Default::default(),
-            Default::default(),
-            kanban_address.clone(),
-        );
-        let employee2 = Employee::new(
-            EmployeeType::Developer,
-            "Anton",
-            EmployeeCharacteristics::new(),
-            EmployeeResources {
-                energy: 50.0,
-                focus: 80.0,
-                stress: 10.0,
-            },
-            kanban_address.clone(),
-        );
-
-        kanban_address.do_send(AddEmployee {
-            employee_address: employee1.addr,
-        });
-        kanban_address.do_send(AddEmployee {
-            employee_address: employee2.addr,
-        });
+        let kanban_rng = StdRng::from_rng(&mut rng).expect("Could not seed kanban.");
+        let kanban_address = Kanban::new(
+            seed,
+            kanban_rng,
+            scenario.clone(),
+            options,
+            report,
+            subscribers,
+        )
+        .start();
 
-        let feature_task = TaskId::CreatePR.to_task().as_feature();
+        for (index, spec) in scenario.employees.iter().enumerate() {
+            let characteristics = spec
+                .characteristics
+                .unwrap_or_else(|| EmployeeCharacteristics::new(&mut rng));
+            let employee_data = EmployeeActor::new(
+                EmployeeId(index as u32),
+                spec.employee_type,
+                scenario.role(spec.employee_type),
+                spec.name.clone(),
+                characteristics,
+                spec.resources,
+                rng.gen(),
+            );
+            let employee = Employee::new(employee_data.clone());
 
-        kanban_address.do_send(feature_task);
-        kanban_address.do_send(feature_task);
+            kanban_address.do_send(AddEmployee {
+                employee_address: employee.addr,
+                employee_data,
+                avatar: spec.avatar_art.clone(),
+            });
+        }
     });
 
     system.run().expect("Something went wrong starting system.");
+
+    summary
+        .recv()
+        .expect("The simulation stopped without a summary")
+}
+
+/// Loads the scenario at `path`, or the built-in one, along with its source for run records.
+fn load_scenario(path: Option<&Path>) -> Result<(Scenario, Option<String>), Box<dyn Error>> {
+    match path {
+        Some(path) => {
+            let source = Scenario::read_source(path)?;
+            Ok((Scenario::from_source(path, &source)?, Some(source)))
+        }
+        None => Ok((Scenario::builtin(), None)),
+    }
+}
+
+fn run(args: RunArgs) -> Result<(), Box<dyn Error>> {
+    let (scenario, source) = load_scenario(args.scenario.as_deref())?;
+
+    let seed = args.seed.or(scenario.seed).unwrap_or_else(rand::random);
+    let run_length = args
+        .length
+        .run_length()
+        .or(scenario.duration_days.map(RunLength::Days))
+        .or(args.headless.then_some(RunLength::Days(1)));
+    let options = RunOptions {
+        headless: args.headless,
+        pace: args.pace.pace(args.headless),
+        run_length,
+    };
+
+    // Events are written on their own thread, the file is done once the run drops its end of
+    // the channel
+    let mut subscribers: Vec<Box<dyn EventSubscriber>> = vec![];
+    let event_writer = match &args.events {
+        Some(path) => {
+            let file = BufWriter::new(File::create(path)?);
+            let (sender, receiver) = mpsc::channel();
+            subscribers.push(Box::new(sender));
+            Some(thread::spawn(move || write_json_lines(receiver, file)))
+        }
+        None => None,
+    };
+
+    let summary = simulate(seed, &scenario, options, subscribers);
+    if let Some(event_writer) = event_writer {
+        event_writer
+            .join()
+            .map_err(|_| "Writing the events failed")??;
+    }
+
+    match &args.summary {
+        Some(path) => fs::write(path, summary.to_string())?,
+        None if args.headless => print!("{}", summary),
+        None => {}
+    }
+    if let Some(path) = &args.history {
+        fs::write(path, summary.history_csv())?;
+    }
+    if let Some(path) = &args.record {
+        let record = RunRecord {
+            seed,
+            scenario_path: args.scenario.clone(),
+            scenario: source,
+            summary,
+        };
+        fs::write(path, serde_json::to_string_pretty(&record)?)?;
+    }
+
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> Result<(), Box<dyn Error>> {
+    let record: RunRecord = serde_json::from_str(&fs::read_to_string(&args.record)?)?;
+    let scenario = match (&record.scenario_path, &record.scenario) {
+        (path, Some(source)) => Scenario::from_source(
+            path.as_deref()
+                .unwrap_or_else(|| Path::new("recorded scenario")),
+            source,
+        )?,
+        (_, None) => Scenario::builtin(),
+    };
+
+    let headless = !args.watch;
+    let pace = args.pace.pace(headless);
+    if headless && pace == Pace::Step {
+        return Err("--step needs --watch".into());
+    }
+    let options = RunOptions {
+        headless,
+        pace,
+        run_length: Some(RunLength::Ticks(record.summary.clock.ticks())),
+    };
+
+    let summary = simulate(record.seed, &scenario, options, vec![]);
+
+    if summary == record.summary {
+        println!(
+            "Replay of seed {} matches the recorded run over {} ticks",
+            record.seed,
+            summary.clock.ticks()
+        );
+        Ok(())
+    } else {
+        println!("Recorded:\n{}\nReplayed:\n{}", record.summary, summary);
+        Err("Replay diverged from the recorded run".into())
+    }
+}
+
+fn validate_scenario(path: &Path) -> Result<(), Box<dyn Error>> {
+    let scenario = Scenario::load(path)?;
+    println!(
+        "{} is valid: {} employees, {} task types",
+        path.display(),
+        scenario.employees.len(),
+        scenario.tasks.len()
+    );
+
+    Ok(())
+}
+
+fn batch(args: BatchArgs) -> Result<(), Box<dyn Error>> {
+    let (scenario, _source) = load_scenario(args.scenario.as_deref())?;
+    let options = RunOptions {
+        headless: true,
+        pace: Pace::Fast,
+        run_length: Some(
+            args.length
+                .run_length()
+                .or(scenario.duration_days.map(RunLength::Days))
+                .unwrap_or(RunLength::Days(1)),
+        ),
+    };
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(stdout()),
+    };
+    // Seeds wrap around past the largest one rather than overflow
+    for seed in (0..args.runs).map(|run| args.seed.wrapping_add(run)) {
+        let summary = simulate(seed, &scenario, options.clone(), vec![]);
+        writeln!(output, "{}", serde_json::to_string(&summary)?)?;
+    }
+    output.flush()?;
+
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command.unwrap_or(Command::Run(RunArgs::default())) {
+        Command::Run(args) => run(args),
+        Command::Replay(args) => replay(args),
+        Command::ValidateScenario {
+            path,
+        } => validate_scenario(&path),
+        Command::Batch(args) => batch(args),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
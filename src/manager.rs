@@ -0,0 +1,143 @@
+use actix::{Handler, Message, MessageResult, SyncContext};
+use rand::{seq::SliceRandom, Rng};
+use serde::Serialize;
+
+use crate::{employee::EmployeeActor, workflow::WorkItemKind};
+
+/// Energy a manager spends on a tick of management, focus goes twice as fast
+static MANAGEMENT_EFFORT: f32 = 0.1;
+
+/// Focus a meeting costs every developer when run by a manager without any rigor
+static MEETING_FOCUS_COST: f32 = 15.;
+
+/// Stress a meeting takes off every developer when run by the most experienced manager
+static MEETING_STRESS_RELIEF: f32 = 10.;
+
+/// Backlog item as a manager sees it when triaging
+#[derive(Debug, Copy, Clone)]
+pub struct BacklogItem {
+    pub uuid: usize,
+    pub kind: WorkItemKind,
+    /// Ticks since it showed up on the board
+    pub waiting: u64,
+    pub priority: u32,
+}
+
+/// What is going on on the board, sent to every manager on every tick
+pub struct Manage {
+    /// Work items nobody started yet
+    pub backlog: Vec<BacklogItem>,
+    /// Uuids of the tasks held up by an impediment
+    pub impeded: Vec<usize>,
+    /// Average stress of the developers
+    pub team_stress: f32,
+}
+
+impl Message for Manage {
+    type Result = ManagementDone;
+}
+
+/// What a manager did with their tick
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub enum ManagerAction {
+    /// Raise the priority of a backlog item
+    Triage { uuid: usize },
+    /// Clear the impediment holding up a task
+    Unblock { uuid: usize },
+    /// Get the developers together, costing them focus and taking some of their stress away
+    Meeting { focus_cost: f32, stress_relief: f32 },
+}
+
+pub struct ManagementDone {
+    pub action: Option<ManagerAction>,
+    pub employee_data: EmployeeActor,
+}
+
+impl EmployeeActor {
+    /// Managers clear impediments first, then call a meeting when the team is stressed and triage
+    /// the backlog otherwise. Experience makes unblocking and meetings more effective, rigor
+    /// shortens meetings and makes triage pick the item that matters most.
+    fn manage(&mut self, manage: &Manage) -> Option<ManagerAction> {
+        let experience = self.characteristics.company_experience / 100.;
+        let rigor = self.characteristics.rigor / 100.;
+
+        if let Some(uuid) = manage.impeded.first() {
+            if self
+                .rng
+                .gen_bool((0.1 + 0.4 * experience as f64).clamp(0., 1.))
+            {
+                return Some(ManagerAction::Unblock {
+                    uuid: *uuid,
+                });
+            }
+            return None;
+        }
+
+        let meeting_chance = 0.01 + 0.1 * (manage.team_stress / 100.).clamp(0., 1.) as f64;
+        if self.rng.gen_bool(meeting_chance) {
+            return Some(ManagerAction::Meeting {
+                focus_cost: MEETING_FOCUS_COST * (1.5 - rigor),
+                stress_relief: MEETING_STRESS_RELIEF * experience,
+            });
+        }
+
+        let untriaged = manage
+            .backlog
+            .iter()
+            .filter(|item| item.priority == 0)
+            .collect::<Vec<_>>();
+        if untriaged.is_empty() || !self.rng.gen_bool(0.1) {
+            return None;
+        }
+        let item = if self.rng.gen_bool(rigor.clamp(0., 1.) as f64) {
+            // Bug fixes first, then whatever waited the longest
+            untriaged
+                .iter()
+                .max_by_key(|item| (item.kind == WorkItemKind::BugFix, item.waiting))
+        } else {
+            untriaged.choose(&mut self.rng)
+        };
+
+        item.map(|item| ManagerAction::Triage {
+            uuid: item.uuid,
+        })
+    }
+}
+
+impl Handler<Manage> for EmployeeActor {
+    type Result = MessageResult<Manage>;
+
+    fn handle(&mut self, manage: Manage, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        let action = self.manage(&manage);
+
+        let effort = MANAGEMENT_EFFORT * (1.5 - self.characteristics.fitness / 100.);
+        self.resources.energy -= effort;
+        self.resources.focus -= effort * 2.;
+        self.resources.clamp();
+
+        MessageResult(ManagementDone {
+            action,
+            employee_data: self.report(),
+        })
+    }
+}
+
+/// A meeting a developer has to sit through
+pub struct Meeting {
+    pub focus_cost: f32,
+    pub stress_relief: f32,
+}
+
+impl Message for Meeting {
+    type Result = ();
+}
+
+impl Handler<Meeting> for EmployeeActor {
+    type Result = ();
+
+    fn handle(&mut self, meeting: Meeting, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.resources.focus -= meeting.focus_cost;
+        self.resources.stress -= meeting.stress_relief;
+        self.resources.clamp();
+    }
+}
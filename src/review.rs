@@ -0,0 +1,80 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::employee::EmployeeActor;
+
+/// Size of a rework task compared to writing the change in the first place
+pub static REWORK_SIZE: f32 = 0.4;
+
+/// Chance for the strictest reviewer to ask for changes to a clean change by the sloppiest author
+static MAX_NITPICK_CHANCE: f64 = 0.3;
+
+/// Chance for the strictest reviewer to throw out a change with defects by the sloppiest author
+static MAX_REJECT_CHANCE: f64 = 0.2;
+
+/// What a finished review decided about a change
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub enum ReviewOutcome {
+    /// Good to go, the work item moves on to merging
+    Approved,
+    /// The authors go over their change once more, then it gets reviewed again
+    ChangesRequested,
+    /// The change is thrown away and written again from scratch
+    Rejected,
+}
+
+/// How the reviews of a run went
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewCounts {
+    pub approved: u32,
+    pub changes_requested: u32,
+    pub rejected: u32,
+}
+
+impl ReviewCounts {
+    pub fn count(&mut self, outcome: ReviewOutcome) {
+        match outcome {
+            ReviewOutcome::Approved => self.approved += 1,
+            ReviewOutcome::ChangesRequested => self.changes_requested += 1,
+            ReviewOutcome::Rejected => self.rejected += 1,
+        }
+    }
+}
+
+/// Decides a review. Defects found always send the change back, and a poor enough change gets
+/// thrown out. Clean changes by sloppy authors may still come back from strict reviewers.
+pub fn review_outcome(
+    authors: &[&EmployeeActor],
+    reviewers: &[&EmployeeActor],
+    defects_found: u32,
+    rng: &mut StdRng,
+) -> ReviewOutcome {
+    if authors.is_empty() || reviewers.is_empty() {
+        return ReviewOutcome::Approved;
+    }
+
+    let quality = authors
+        .iter()
+        .map(|author| {
+            (author.characteristics.rigor + author.characteristics.programming_skills) / 200.
+        })
+        .sum::<f32>() as f64
+        / authors.len() as f64;
+    let strictness = reviewers
+        .iter()
+        .map(|reviewer| reviewer.characteristics.rigor / 100.)
+        .fold(0., f32::max) as f64;
+    let sloppiness = (1. - quality).clamp(0., 1.) * strictness.clamp(0., 1.);
+
+    if defects_found > 0 {
+        if rng.gen_bool(MAX_REJECT_CHANCE * sloppiness) {
+            ReviewOutcome::Rejected
+        } else {
+            ReviewOutcome::ChangesRequested
+        }
+    } else if rng.gen_bool(MAX_NITPICK_CHANCE * sloppiness) {
+        ReviewOutcome::ChangesRequested
+    } else {
+        ReviewOutcome::Approved
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::task::TaskId;
+
+/// Stages an employee type works on, and how fast
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleDefinition {
+    /// Stages they can be given, coffee breaks are open to everyone who takes tasks
+    pub allowed: Vec<TaskId>,
+    /// Stages they get before anything else they are allowed to do
+    #[serde(default)]
+    pub preferred: Vec<TaskId>,
+    /// Multiplier on their output for some stages, 1 for the others
+    #[serde(default)]
+    pub speed: BTreeMap<TaskId, f32>,
+}
+
+impl RoleDefinition {
+    /// Whether the role takes tasks at all, managers for one don't
+    pub fn takes_tasks(&self) -> bool {
+        !self.allowed.is_empty()
+    }
+
+    pub fn allows(&self, id: TaskId) -> bool {
+        self.allowed.contains(&id) || (id == TaskId::CoffeeBreak && self.takes_tasks())
+    }
+
+    /// Whether they go for `id` before anything else, everyone who takes tasks goes for a coffee
+    /// break first
+    pub fn prefers(&self, id: TaskId) -> bool {
+        self.preferred.contains(&id) || (id == TaskId::CoffeeBreak && self.takes_tasks())
+    }
+
+    pub fn speed(&self, id: TaskId) -> f32 {
+        self.speed.get(&id).copied().unwrap_or(1.)
+    }
+
+    pub fn validate(&self, context: &str, problems: &mut Vec<String>) {
+        if self.allowed.contains(&TaskId::CoffeeBreak) {
+            problems.push(format!(
+                "{}: CoffeeBreak is always allowed, it can't be listed",
+                context
+            ));
+        }
+        for id in self.preferred.iter() {
+            if !self.allowed.contains(id) {
+                problems.push(format!(
+                    "{}: {:?} is preferred but not allowed",
+                    context, id
+                ));
+            }
+        }
+        for (id, speed) in self.speed.iter() {
+            if *speed <= 0. {
+                problems.push(format!("{}: speed of {:?} has to be positive", context, id));
+            }
+        }
+    }
+}
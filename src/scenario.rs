@@ -0,0 +1,508 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    clock::{WorkingHours, DEFAULT_MINUTES_PER_TICK, MINUTES_PER_DAY},
+    column::WipLimits,
+    deadline::DueDays,
+    employee::{EmployeeCharacteristics, EmployeeResources, EmployeeType},
+    role::RoleDefinition,
+    scheduler::Scheduler,
+    staffing::StaffingEvent,
+    task::{Task, TaskEnergyMultipliers, TaskId},
+    workflow::{WorkItemKind, Workflows},
+};
+
+static DEFAULT_SCENARIO: &str = include_str!("../scenarios/default.toml");
+
+/// Avatars of the built-in scenario, by lower case name, so that it never has to read the disk
+static BUILTIN_AVATARS: [(&str, &str); 2] = [
+    ("okko", include_str!("../scenarios/avatars/okko.txt")),
+    ("anton", include_str!("../scenarios/avatars/anton.txt")),
+];
+
+/// Where the art of the employees of a scenario comes from
+enum Avatars<'a> {
+    /// Files relative to `base_dir`, or named after the employees in `avatar_dir`
+    Files {
+        base_dir: &'a Path,
+        avatar_dir: Option<&'a Path>,
+    },
+    /// Compiled in along with the built-in scenario
+    Builtin,
+}
+
+/// Everything that describes a run: the team, the kinds of tasks and how work shows up.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub seed: Option<u64>,
+    #[serde(default = "default_minutes_per_tick")]
+    pub minutes_per_tick: u64,
+    pub duration_days: Option<u64>,
+    /// Directory with the avatars of the employees who don't name one, as `<name>.txt` files
+    /// relative to the scenario file
+    pub avatar_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub working_hours: WorkingHours,
+    #[serde(default)]
+    pub spawn: SpawnRates,
+    pub employees: Vec<EmployeeSpec>,
+    #[serde(default)]
+    pub tasks: BTreeMap<TaskId, TaskDefinition>,
+    #[serde(default)]
+    pub workflows: Workflows,
+    /// How open tasks are handed out to the employees
+    #[serde(default)]
+    pub scheduler: Scheduler,
+    #[serde(default)]
+    pub wip_limits: WipLimits,
+    /// Days work items of every kind are due in
+    #[serde(default)]
+    pub due_days: DueDays,
+    /// Stages every employee type works on
+    #[serde(default)]
+    pub roles: BTreeMap<EmployeeType, RoleDefinition>,
+    /// People joining and leaving during the run, in day order once loaded
+    #[serde(default)]
+    pub staffing: Vec<StaffingEvent>,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct SpawnRates {
+    /// Chance for a new work item to show up on every tick
+    pub chance_per_tick: f64,
+    /// Share of the new work items that are chores
+    pub chore_chance: f64,
+    /// Chance on every tick for a started task to get held up by an impediment
+    pub impediment_chance: f64,
+    /// Chance for a new work item to depend on one already on the board
+    pub dependency_chance: f64,
+    /// Chance for a new work item to wait on a delivery from another team
+    pub other_team_chance: f64,
+    pub initial_features: usize,
+    pub initial_bug_fixes: usize,
+}
+
+impl Default for SpawnRates {
+    fn default() -> Self {
+        SpawnRates {
+            chance_per_tick: 0.01,
+            chore_chance: 0.0,
+            impediment_chance: 0.005,
+            dependency_chance: 0.0,
+            other_team_chance: 0.0,
+            initial_features: 2,
+            initial_bug_fixes: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmployeeSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub employee_type: EmployeeType,
+    /// Rolled from the seed when not given
+    pub characteristics: Option<EmployeeCharacteristics>,
+    #[serde(default)]
+    pub resources: EmployeeResources,
+    /// Text file with the ASCII art of the employee, relative to the scenario file. Taken from
+    /// `avatar_dir` when left out, and generated from their name when not found there either
+    pub avatar: Option<PathBuf>,
+    /// Art of the employee found when the scenario is loaded
+    #[serde(skip)]
+    pub avatar_art: Option<String>,
+}
+
+impl EmployeeSpec {
+    fn validate(&self, context: &str, problems: &mut Vec<String>) {
+        if self.name.trim().is_empty() {
+            problems.push(format!("{}: name can't be empty", context));
+        }
+        if let Some(characteristics) = self.characteristics {
+            for (field, value) in [
+                ("company_experience", characteristics.company_experience),
+                ("rigor", characteristics.rigor),
+                ("programming_skills", characteristics.programming_skills),
+                ("fitness", characteristics.fitness),
+            ] {
+                check_range(problems, context, field, value, 0.0, 100.0);
+            }
+        }
+        for (field, value) in [
+            ("energy", self.resources.energy),
+            ("focus", self.resources.focus),
+            ("stress", self.resources.stress),
+        ] {
+            check_range(problems, context, field, value, 0.0, 100.0);
+        }
+    }
+
+    /// Reads the avatar file, relative to `base_dir`. Without one, looks for `<name>.txt` in
+    /// `avatar_dir`, in lower case too, and leaves the art out when there is none. The built-in
+    /// scenario looks among its own avatars instead.
+    fn read_avatar(&mut self, avatars: &Avatars, context: &str, problems: &mut Vec<String>) {
+        let (base_dir, avatar_dir) = match *avatars {
+            Avatars::Files {
+                base_dir,
+                avatar_dir,
+            } => (base_dir, avatar_dir),
+            Avatars::Builtin => {
+                let name = self.name.to_lowercase();
+                self.avatar_art = BUILTIN_AVATARS
+                    .iter()
+                    .find(|(builtin, _)| *builtin == name)
+                    .map(|(_, art)| art.to_string());
+                return;
+            }
+        };
+
+        if let Some(avatar) = &self.avatar {
+            let avatar_path = base_dir.join(avatar);
+            match fs::read_to_string(&avatar_path) {
+                Ok(art) => self.avatar_art = Some(art),
+                Err(err) => problems.push(format!(
+                    "{}: could not read avatar {}: {}",
+                    context,
+                    avatar_path.display(),
+                    err
+                )),
+            }
+        } else if let Some(avatar_dir) = avatar_dir {
+            self.avatar_art = [self.name.clone(), self.name.to_lowercase()]
+                .iter()
+                .find_map(|name| fs::read_to_string(avatar_dir.join(format!("{}.txt", name))).ok());
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskDefinition {
+    pub total_energy_required: f32,
+    pub energy_taken_per_tick: f32,
+    #[serde(default)]
+    pub multipliers: TaskEnergyMultipliers,
+}
+
+impl TaskDefinition {
+    pub fn to_task(self, id: TaskId) -> Task {
+        Task {
+            id,
+            total_energy_required: self.total_energy_required,
+            estimated_energy: self.total_energy_required,
+            energy_taken_per_tick: self.energy_taken_per_tick,
+            energy_multipliers: self.multipliers,
+            ..Task::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// Every problem found in a scenario that parsed fine
+    Invalid(PathBuf, Vec<String>),
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(path, err) => write!(f, "Could not read {}: {}", path.display(), err),
+            ScenarioError::Parse(path, err) => {
+                write!(f, "Could not parse {}: {}", path.display(), err)
+            }
+            ScenarioError::Invalid(path, problems) => {
+                write!(f, "Invalid scenario {}:", path.display())?;
+                for problem in problems {
+                    write!(f, "\n  - {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+fn default_minutes_per_tick() -> u64 {
+    DEFAULT_MINUTES_PER_TICK
+}
+
+impl Scenario {
+    /// The scenario crunch runs when none is given, `scenarios/default.toml`.
+    pub fn builtin() -> Scenario {
+        Scenario::parse(Path::new("scenarios/default.toml"), DEFAULT_SCENARIO, None)
+            .expect("The built-in scenario is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        let content = Scenario::read_source(path)?;
+
+        Scenario::from_source(path, &content)
+    }
+
+    pub fn read_source(path: &Path) -> Result<String, ScenarioError> {
+        fs::read_to_string(path).map_err(|err| ScenarioError::Io(path.to_path_buf(), err))
+    }
+
+    /// Loads a scenario from its content, `path` is only used to find avatars and in errors.
+    pub fn from_source(path: &Path, content: &str) -> Result<Scenario, ScenarioError> {
+        Scenario::parse(path, content, Some(Scenario::builtin()))
+    }
+
+    /// Parses and validates `content`. Task types it does not define are taken from `fallback`.
+    fn parse(
+        path: &Path,
+        content: &str,
+        fallback: Option<Scenario>,
+    ) -> Result<Scenario, ScenarioError> {
+        let mut scenario: Scenario =
+            toml::from_str(content).map_err(|err| ScenarioError::Parse(path.to_path_buf(), err))?;
+
+        // Only the built-in scenario has nothing to fall back on
+        let is_builtin = fallback.is_none();
+        if let Some(fallback) = fallback {
+            for (id, definition) in fallback.tasks {
+                scenario.tasks.entry(id).or_insert(definition);
+            }
+            scenario.workflows.merge(fallback.workflows);
+            for (employee_type, role) in fallback.roles {
+                scenario.roles.entry(employee_type).or_insert(role);
+            }
+        }
+
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let avatar_dir = scenario.avatar_dir.as_ref().map(|dir| base_dir.join(dir));
+        let avatars = if is_builtin {
+            Avatars::Builtin
+        } else {
+            Avatars::Files {
+                base_dir,
+                avatar_dir: avatar_dir.as_deref(),
+            }
+        };
+        let mut problems = scenario.validate();
+        for (index, employee) in scenario.employees.iter_mut().enumerate() {
+            let context = format!("employees[{}] ({})", index, employee.name);
+            employee.read_avatar(&avatars, &context, &mut problems);
+        }
+        for (index, event) in scenario.staffing.iter_mut().enumerate() {
+            if let Some(employee) = &mut event.hire {
+                let context = format!("staffing[{}].hire ({})", index, employee.name);
+                employee.read_avatar(&avatars, &context, &mut problems);
+            }
+        }
+        scenario.staffing.sort_by_key(|event| event.day);
+
+        if problems.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(ScenarioError::Invalid(path.to_path_buf(), problems))
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        if !MINUTES_PER_DAY.is_multiple_of(self.minutes_per_tick) {
+            problems.push(format!(
+                "minutes_per_tick has to divide a day ({} minutes), got {}",
+                MINUTES_PER_DAY, self.minutes_per_tick
+            ));
+        }
+        if self.duration_days == Some(0) {
+            problems.push("duration_days has to be at least 1".to_string());
+        }
+        self.working_hours.validate(&mut problems);
+
+        check_chance(
+            &mut problems,
+            "spawn.chance_per_tick",
+            self.spawn.chance_per_tick,
+        );
+        check_chance(&mut problems, "spawn.chore_chance", self.spawn.chore_chance);
+        check_chance(
+            &mut problems,
+            "spawn.impediment_chance",
+            self.spawn.impediment_chance,
+        );
+        check_chance(
+            &mut problems,
+            "spawn.dependency_chance",
+            self.spawn.dependency_chance,
+        );
+        check_chance(
+            &mut problems,
+            "spawn.other_team_chance",
+            self.spawn.other_team_chance,
+        );
+
+        if self.employees.is_empty() {
+            problems.push("at least one employee is needed".to_string());
+        }
+        for (index, employee) in self.employees.iter().enumerate() {
+            let context = format!("employees[{}] ({})", index, employee.name);
+            employee.validate(&context, &mut problems);
+        }
+        self.validate_staffing(&mut problems);
+
+        for (id, definition) in self.tasks.iter() {
+            let context = format!("tasks.{:?}", id);
+            if definition.total_energy_required <= 0.0 {
+                problems.push(format!(
+                    "{}: total_energy_required has to be positive",
+                    context
+                ));
+            }
+            if definition.energy_taken_per_tick <= 0.0 {
+                problems.push(format!(
+                    "{}: energy_taken_per_tick has to be positive",
+                    context
+                ));
+            }
+            let multipliers = definition.multipliers;
+            for (field, value) in [
+                ("company_experience", multipliers.company_experience),
+                ("rigor", multipliers.rigor),
+                ("programming_skills", multipliers.programming_skills),
+                ("fitness", multipliers.fitness),
+                ("energy", multipliers.energy),
+                ("focus", multipliers.focus),
+                ("stress", multipliers.stress),
+            ] {
+                if value < 0.0 {
+                    problems.push(format!(
+                        "{}: multiplier {} can't be negative",
+                        context, field
+                    ));
+                }
+            }
+        }
+        for id in TaskId::ALL {
+            if !self.tasks.contains_key(&id) {
+                problems.push(format!("tasks.{:?} is not defined", id));
+            }
+        }
+        self.workflows.validate(&mut problems);
+        for employee_type in EmployeeType::ALL {
+            match self.roles.get(&employee_type) {
+                Some(role) => role.validate(&format!("roles.{:?}", employee_type), &mut problems),
+                None => problems.push(format!("roles.{:?} is not defined", employee_type)),
+            }
+        }
+        self.wip_limits.validate(&mut problems);
+        self.due_days.validate(&mut problems);
+
+        problems
+    }
+
+    /// Checks that every staffing event either hires someone or fires someone who is there by then.
+    /// Names can be shared, but not by the people on the team when one of them is fired.
+    fn validate_staffing(&self, problems: &mut Vec<String>) {
+        let mut team = BTreeMap::<&str, usize>::new();
+        for employee in self.employees.iter() {
+            *team.entry(employee.name.as_str()).or_default() += 1;
+        }
+        let mut events = self.staffing.iter().enumerate().collect::<Vec<_>>();
+        events.sort_by_key(|(_, event)| event.day);
+        for (index, event) in events {
+            let context = format!("staffing[{}]", index);
+            match (&event.hire, &event.fire) {
+                (Some(employee), None) => {
+                    let context = format!("{}.hire ({})", context, employee.name);
+                    employee.validate(&context, problems);
+                    *team.entry(employee.name.as_str()).or_default() += 1;
+                }
+                (None, Some(name)) => match team.get_mut(name.as_str()) {
+                    Some(1) => {
+                        team.remove(name.as_str());
+                    }
+                    Some(count) => {
+                        problems.push(format!(
+                            "{}: {} employees are called {} on day {}",
+                            context, count, name, event.day
+                        ));
+                        *count -= 1;
+                    }
+                    None => problems.push(format!(
+                        "{}: {} is not on the team on day {}",
+                        context, name, event.day
+                    )),
+                },
+                _ => problems.push(format!(
+                    "{}: has to either hire someone or fire someone",
+                    context
+                )),
+            }
+            if self
+                .duration_days
+                .is_some_and(|duration_days| event.day >= duration_days)
+            {
+                problems.push(format!(
+                    "{}: day {} is after the end of the run",
+                    context, event.day
+                ));
+            }
+        }
+    }
+
+    pub fn role(&self, employee_type: EmployeeType) -> RoleDefinition {
+        self.roles[&employee_type].clone()
+    }
+
+    pub fn task(&self, id: TaskId) -> Task {
+        self.tasks[&id].to_task(id)
+    }
+
+    /// Task of the first stage of a new work item of `kind`.
+    pub fn work_item(&self, kind: WorkItemKind) -> Task {
+        let first_stage = self.workflows.stages(kind)[0];
+        self.task(first_stage).as_stage(kind, 0)
+    }
+
+    /// Task of the stage following `task` in its workflow, `None` when the work item is done.
+    pub fn next_stage(&self, task: &Task) -> Option<Task> {
+        self.workflows
+            .next_stage(task.kind, task.stage)
+            .map(|id| Task {
+                priority: task.priority,
+                ..self.task(id).as_stage(task.kind, task.stage + 1)
+            })
+    }
+}
+
+fn check_chance(problems: &mut Vec<String>, field: &str, value: f64) {
+    if !(0.0..=1.0).contains(&value) {
+        problems.push(format!(
+            "{} is a chance between 0 and 1, got {}",
+            field, value
+        ));
+    }
+}
+
+fn check_range(
+    problems: &mut Vec<String>,
+    context: &str,
+    field: &str,
+    value: f32,
+    min: f32,
+    max: f32,
+) {
+    if !(min..=max).contains(&value) {
+        problems.push(format!(
+            "{}: {} has to be between {} and {}, got {}",
+            context, field, min, max, value
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, seq::SliceRandom};
+use serde::Deserialize;
+
+use crate::{
+    employee::{EmployeeActor, EmployeeId},
+    kanban::TaskEntry,
+    task::{Task, TaskId},
+    workflow::WorkItem,
+};
+
+/// What an assignment strategy gets to look at on every tick
+pub struct Board<'a> {
+    /// Open tasks, highest priority first
+    pub tasks: &'a [TaskEntry],
+    /// Work items still going through their workflow, by uuid
+    pub work_items: &'a BTreeMap<usize, WorkItem>,
+    /// Latest known state of the employees who can take tasks, by id
+    pub employees: Vec<&'a EmployeeActor>,
+}
+
+impl Board<'_> {
+    /// Whether `employee` can be given the task with `uuid`. Their role has to allow it, and rework
+    /// goes to the authors of the change while any of them is available.
+    fn allows(&self, employee: &EmployeeActor, uuid: usize, task: &Task) -> bool {
+        if !employee.role.allows(task.id) {
+            return false;
+        }
+        if !task.rework {
+            return true;
+        }
+        let Some(work_item) = self.work_items.get(&uuid) else {
+            return true;
+        };
+
+        let authors = work_item.authors();
+        authors.contains(&employee.employee_id)
+            || !self
+                .employees
+                .iter()
+                .any(|other| authors.contains(&other.employee_id) && other.role.allows(task.id))
+    }
+}
+
+/// Hands out the open tasks to the employees for a tick. Whatever the strategy, employees only get
+/// the stages their role allows.
+pub trait AssignmentStrategy {
+    /// Pairs of employee id and task uuid, in the order the work should be applied. Every
+    /// employee and every task shows up once at most, employees left out sit idle for the tick.
+    fn assign(&self, board: &Board, rng: &mut StdRng) -> Vec<(EmployeeId, usize)>;
+}
+
+/// Assignment strategy a scenario runs with
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scheduler {
+    /// Every employee in turn takes the highest priority task they can, preferred stages first
+    #[default]
+    PriorityFirst,
+    /// Every task, highest priority first, to the free employee who gets through it fastest
+    SkillMatching,
+    /// Every task, highest priority first, to the free employee involved in the fewest open tasks
+    LeastLoaded,
+    /// Every employee in turn takes a random task they can, preferred stages first, whatever its
+    /// priority
+    Random,
+    /// Like priority-first, but nobody reviews a change they wrote
+    NoSelfReview,
+    /// Like priority-first, with the tasks expected to need the least work left going first
+    ShortestFirst,
+}
+
+impl Scheduler {
+    pub fn strategy(&self) -> Box<dyn AssignmentStrategy> {
+        match self {
+            Scheduler::PriorityFirst => Box::new(PriorityFirst),
+            Scheduler::SkillMatching => Box::new(SkillMatching),
+            Scheduler::LeastLoaded => Box::new(LeastLoaded),
+            Scheduler::Random => Box::new(Random),
+            Scheduler::NoSelfReview => Box::new(NoSelfReview),
+            Scheduler::ShortestFirst => Box::new(ShortestFirst),
+        }
+    }
+}
+
+pub struct PriorityFirst;
+
+impl AssignmentStrategy for PriorityFirst {
+    fn assign(&self, board: &Board, _rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        pick_for_every_employee(board, &board.employees, |_, _, _| true)
+    }
+}
+
+pub struct SkillMatching;
+
+impl AssignmentStrategy for SkillMatching {
+    fn assign(&self, board: &Board, _rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        pick_for_every_task(board, |_, task, employee| {
+            task.energy_multipliers
+                .get_energy_cost(&employee.characteristics, &employee.resources)
+                * employee.role.speed(task.id)
+        })
+    }
+}
+
+pub struct LeastLoaded;
+
+impl AssignmentStrategy for LeastLoaded {
+    fn assign(&self, board: &Board, _rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        pick_for_every_task(board, |board, _, employee| {
+            let load = board
+                .tasks
+                .iter()
+                .filter(|(_, (_, contributors))| contributors.contains(&employee.employee_id))
+                .count();
+            -(load as f32)
+        })
+    }
+}
+
+pub struct Random;
+
+impl AssignmentStrategy for Random {
+    fn assign(&self, board: &Board, rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        let mut tasks = board.tasks.to_vec();
+        tasks.shuffle(rng);
+        let board = Board {
+            tasks: &tasks,
+            work_items: board.work_items,
+            employees: board.employees.clone(),
+        };
+
+        pick_for_every_employee(&board, &board.employees, |_, _, _| true)
+    }
+}
+
+pub struct NoSelfReview;
+
+impl AssignmentStrategy for NoSelfReview {
+    fn assign(&self, board: &Board, _rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        pick_for_every_employee(board, &board.employees, |employee, uuid, task| {
+            !is_own_review(board, employee.employee_id, uuid, task)
+        })
+    }
+}
+
+pub struct ShortestFirst;
+
+impl AssignmentStrategy for ShortestFirst {
+    fn assign(&self, board: &Board, _rng: &mut StdRng) -> Vec<(EmployeeId, usize)> {
+        // Only the estimates are known, a task going past its own moves back once re-estimated
+        let mut tasks = board.tasks.to_vec();
+        tasks.sort_by(|(_, (a, _)), (_, (b, _))| {
+            a.estimated_energy_left()
+                .total_cmp(&b.estimated_energy_left())
+        });
+        let board = Board {
+            tasks: &tasks,
+            work_items: board.work_items,
+            employees: board.employees.clone(),
+        };
+
+        pick_for_every_employee(&board, &board.employees, |_, _, _| true)
+    }
+}
+
+fn is_own_review(board: &Board, employee_id: EmployeeId, uuid: usize, task: &Task) -> bool {
+    task.id == TaskId::ReviewPR
+        && board
+            .work_items
+            .get(&uuid)
+            .is_some_and(|work_item| work_item.authors().contains(&employee_id))
+}
+
+/// Lets every employee, in the given order, take the highest priority task left that their role
+/// allows and `can_take` agrees with, looking at their preferred stages first.
+fn pick_for_every_employee<F>(
+    board: &Board,
+    employees: &[&EmployeeActor],
+    can_take: F,
+) -> Vec<(EmployeeId, usize)>
+where
+    F: Fn(&EmployeeActor, usize, &Task) -> bool,
+{
+    let mut taken = vec![false; board.tasks.len()];
+    let mut assignments = vec![];
+    for employee in employees {
+        let available = |index: &usize| {
+            let (uuid, (task, _)) = &board.tasks[*index];
+            !taken[*index] && board.allows(employee, *uuid, task) && can_take(employee, *uuid, task)
+        };
+        let pick = (0..board.tasks.len())
+            .filter(available)
+            .find(|index| employee.role.prefers(board.tasks[*index].1 .0.id))
+            .or_else(|| (0..board.tasks.len()).find(available));
+
+        if let Some(index) = pick {
+            taken[index] = true;
+            assignments.push((employee.employee_id, board.tasks[index].0));
+        }
+    }
+
+    assignments
+}
+
+/// Goes through the tasks highest priority first and gives each one to the free employee with the
+/// best score among the ones whose role allows it, employees preferring the stage going first and
+/// ties going to the first one by id.
+fn pick_for_every_task<F>(board: &Board, score: F) -> Vec<(EmployeeId, usize)>
+where
+    F: Fn(&Board, &Task, &EmployeeActor) -> f32,
+{
+    let mut free = board.employees.clone();
+    let mut assignments = vec![];
+    for (uuid, (task, _)) in board.tasks.iter() {
+        let mut best: Option<(usize, (bool, f32))> = None;
+        for (index, employee) in free.iter().enumerate() {
+            if !board.allows(employee, *uuid, task) {
+                continue;
+            }
+            let rank = (employee.role.prefers(task.id), score(board, task, employee));
+            if best.is_none_or(|(_, best_rank)| rank > best_rank) {
+                best = Some((index, rank));
+            }
+        }
+
+        if let Some((index, _)) = best {
+            let employee = free.remove(index);
+            assignments.push((employee.employee_id, *uuid));
+        }
+    }
+
+    assignments
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{scenario::Scenario, workflow::WorkItemKind};
+
+    /// Feature with uuid 1 written by employee 0 and waiting for review, then `changes` features
+    /// nobody started yet
+    fn tasks(changes: usize) -> (Vec<TaskEntry>, BTreeMap<usize, WorkItem>) {
+        let scenario = Scenario::builtin();
+        let mut review = WorkItem::new(WorkItemKind::Feature, 0);
+        review
+            .stages
+            .push((TaskId::CreatePR, BTreeSet::from([EmployeeId(0)])));
+        let mut tasks = vec![(
+            1,
+            (
+                scenario
+                    .task(TaskId::ReviewPR)
+                    .as_stage(WorkItemKind::Feature, 1),
+                BTreeSet::new(),
+            ),
+        )];
+        let mut work_items = BTreeMap::from([(1, review)]);
+        for uuid in 2..changes + 2 {
+            tasks.push((
+                uuid,
+                (scenario.work_item(WorkItemKind::Feature), BTreeSet::new()),
+            ));
+            work_items.insert(uuid, WorkItem::new(WorkItemKind::Feature, 0));
+        }
+
+        (tasks, work_items)
+    }
+
+    #[test]
+    fn no_self_review_keeps_authors_off_their_review() {
+        let (tasks, work_items) = tasks(1);
+        let employees = [EmployeeActor::developer(0), EmployeeActor::developer(1)];
+        let board = Board {
+            tasks: &tasks,
+            work_items: &work_items,
+            employees: employees.iter().collect(),
+        };
+
+        let assignments = NoSelfReview.assign(&board, &mut StdRng::seed_from_u64(0));
+
+        assert!(!assignments.contains(&(EmployeeId(0), 1)));
+        assert!(assignments.contains(&(EmployeeId(1), 1)));
+    }
+
+    #[test]
+    fn no_self_review_leaves_a_review_without_other_reviewers_open() {
+        let (tasks, work_items) = tasks(0);
+        let employees = [EmployeeActor::developer(0)];
+        let board = Board {
+            tasks: &tasks,
+            work_items: &work_items,
+            employees: employees.iter().collect(),
+        };
+
+        assert!(NoSelfReview
+            .assign(&board, &mut StdRng::seed_from_u64(0))
+            .is_empty());
+    }
+
+    #[test]
+    fn every_employee_and_task_is_assigned_once_at_most() {
+        let schedulers = [
+            Scheduler::PriorityFirst,
+            Scheduler::SkillMatching,
+            Scheduler::LeastLoaded,
+            Scheduler::Random,
+            Scheduler::NoSelfReview,
+            Scheduler::ShortestFirst,
+        ];
+        let employees = (0..4).map(EmployeeActor::developer).collect::<Vec<_>>();
+        // Fewer tasks than employees, then more
+        for changes in [1, 6] {
+            let (tasks, work_items) = tasks(changes);
+            let board = Board {
+                tasks: &tasks,
+                work_items: &work_items,
+                employees: employees.iter().collect(),
+            };
+
+            for scheduler in schedulers {
+                let assignments = scheduler
+                    .strategy()
+                    .assign(&board, &mut StdRng::seed_from_u64(0));
+
+                let employee_ids = assignments
+                    .iter()
+                    .map(|(employee_id, _)| *employee_id)
+                    .collect::<BTreeSet<_>>();
+                let uuids = assignments
+                    .iter()
+                    .map(|(_, uuid)| *uuid)
+                    .collect::<BTreeSet<_>>();
+                assert_eq!(employee_ids.len(), assignments.len(), "{:?}", scheduler);
+                assert_eq!(uuids.len(), assignments.len(), "{:?}", scheduler);
+                assert_eq!(
+                    assignments.len(),
+                    employees.len().min(tasks.len()),
+                    "{:?}",
+                    scheduler
+                );
+            }
+        }
+    }
+}
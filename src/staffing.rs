@@ -0,0 +1,66 @@
+use actix::{Handler, Message, SyncContext};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{employee::EmployeeActor, scenario::EmployeeSpec};
+
+/// Highest company experience a new hire can start with when the scenario doesn't give it
+pub static NEW_HIRE_EXPERIENCE: f32 = 10.;
+
+/// Working days a new hire spends learning the ropes with a mentor
+pub static ONBOARDING_DAYS: u64 = 10;
+
+/// Focus a mentor spends on every tick of work of the new hire they look after
+pub static MENTORING_FOCUS_COST: f32 = 1.;
+
+/// Chance for an employee to quit at the start of a day, for every time they burned out
+static ATTRITION_CHANCE: f64 = 0.1;
+
+/// Someone joining or leaving the team on a given day of the run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StaffingEvent {
+    /// Day of the run it happens on, counting from 0
+    pub day: u64,
+    pub hire: Option<EmployeeSpec>,
+    /// Name of the employee who leaves
+    pub fire: Option<String>,
+}
+
+impl EmployeeActor {
+    /// Whether they are still learning the ropes
+    pub fn is_onboarding(&self) -> bool {
+        self.onboarding_days > 0
+    }
+
+    /// A day of onboarding has gone by
+    pub fn settle_in(&mut self) {
+        self.onboarding_days = self.onboarding_days.saturating_sub(1);
+    }
+
+    /// Employees who burned out may hand in their notice, more likely the more often it happened.
+    pub fn consider_quitting(&mut self) {
+        if self.burnouts > 0 {
+            let chance = (ATTRITION_CHANCE * self.burnouts as f64).clamp(0., 1.);
+            self.resigned = self.rng.gen_bool(chance);
+        }
+    }
+}
+
+/// Time a mentor spent helping the new hire they look after
+pub struct Mentoring {
+    pub focus_cost: f32,
+}
+
+impl Message for Mentoring {
+    type Result = ();
+}
+
+impl Handler<Mentoring> for EmployeeActor {
+    type Result = ();
+
+    fn handle(&mut self, mentoring: Mentoring, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.resources.focus -= mentoring.focus_cost;
+        self.resources.clamp();
+    }
+}
@@ -0,0 +1,148 @@
+use actix::{Handler, Message, SyncContext};
+use rand::Rng;
+
+use crate::employee::EmployeeActor;
+
+/// Stress above which employees start making mistakes, more of them the closer they get to 100
+static MISTAKE_STRESS: f32 = 50.;
+
+/// Chance to undo the work of a tick at full stress
+static MAX_MISTAKE_CHANCE: f64 = 0.05;
+
+/// Stress above which employees can burn out, with this chance on every tick of work
+static BURNOUT_STRESS: f32 = 90.;
+static BURNOUT_CHANCE: f64 = 0.01;
+
+/// Stress a day of sick leave takes away
+static SICK_DAY_RELIEF: f32 = 25.;
+
+/// Things that happen to an employee and change their stress
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StressEvent {
+    /// Worked a tick in a work in progress column that is at its limit, the backlog doesn't count
+    WipOverload,
+    /// Worked a tick after hours
+    Overtime,
+    /// A task they worked on got held up
+    Incident,
+    /// Took a coffee break
+    Break,
+    /// Finished a task
+    FinishedWork,
+    /// Had the weekend off
+    Weekend,
+    /// A change they wrote came back from review with changes to make
+    ChangesRequested,
+    /// A change they wrote was thrown out in review
+    Rejected,
+    /// Worked a tick on a work item past its due date
+    Overdue,
+}
+
+impl StressEvent {
+    /// Change in stress for an employee of average fitness
+    pub fn stress(&self) -> f32 {
+        match self {
+            StressEvent::WipOverload => 0.3,
+            StressEvent::Overtime => 1.5,
+            StressEvent::Incident => 5.,
+            StressEvent::Break => -5.,
+            StressEvent::FinishedWork => -2.,
+            StressEvent::Weekend => -30.,
+            StressEvent::ChangesRequested => 2.,
+            StressEvent::Rejected => 6.,
+            StressEvent::Overdue => 0.2,
+        }
+    }
+}
+
+impl EmployeeActor {
+    /// Applies the stress of `event`, fit employees take pressure better.
+    pub fn feel(&mut self, event: StressEvent) {
+        let stress = event.stress();
+        self.resources.stress += if stress > 0. {
+            stress * (1.5 - self.characteristics.fitness / 100.)
+        } else {
+            stress
+        };
+        self.resources.clamp();
+    }
+
+    /// Whether the tick of work just done went wrong and has to be done again
+    pub fn makes_mistake(&mut self) -> bool {
+        let pressure = ((self.resources.stress - MISTAKE_STRESS) / (100. - MISTAKE_STRESS)).max(0.);
+
+        pressure > 0. && self.rng.gen_bool(MAX_MISTAKE_CHANCE * pressure as f64)
+    }
+
+    /// Sends the employee on sick leave for a few days when stress gets the better of them
+    pub fn check_burnout(&mut self) {
+        if self.resources.stress >= BURNOUT_STRESS && self.rng.gen_bool(BURNOUT_CHANCE) {
+            self.sick_days = self.rng.gen_range(2..=5);
+            self.burnouts += 1;
+        }
+    }
+
+    /// A day of sick leave has gone by
+    pub fn recover(&mut self) {
+        if self.sick_days > 0 {
+            self.sick_days -= 1;
+            self.resources.stress -= SICK_DAY_RELIEF;
+            self.resources.clamp();
+        }
+    }
+}
+
+/// Something that happened to an employee outside of their own work
+pub struct Stress {
+    pub event: StressEvent,
+}
+
+impl Message for Stress {
+    type Result = ();
+}
+
+impl Handler<Stress> for EmployeeActor {
+    type Result = ();
+
+    fn handle(&mut self, stress: Stress, _ctx: &mut SyncContext<Self>) -> Self::Result {
+        self.feel(stress.event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overtime_in_a_full_column_burns_out() {
+        let mut employee = EmployeeActor::developer(1);
+
+        // Three hours of overtime a day for four weeks, with the column at its limit
+        for _ in 0..4 * 5 * 18 {
+            employee.feel(StressEvent::Overtime);
+            employee.feel(StressEvent::WipOverload);
+            employee.check_burnout();
+            if employee.burnouts > 0 {
+                break;
+            }
+        }
+
+        assert_eq!(employee.burnouts, 1);
+        assert!(employee.sick_days > 0);
+    }
+
+    #[test]
+    fn finishing_work_does_not_outweigh_overtime() {
+        let mut employee = EmployeeActor::developer(1);
+
+        // A day of overtime during which a couple of tasks get done
+        for _ in 0..18 {
+            employee.feel(StressEvent::Overtime);
+        }
+        employee.feel(StressEvent::FinishedWork);
+        employee.feel(StressEvent::FinishedWork);
+
+        assert!(employee.resources.stress > 0.);
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::SimClock,
+    defect::DefectCounts,
+    employee::{EmployeeCharacteristics, EmployeeId, EmployeeResources},
+    kanban::Kanban,
+    learning::SkillSample,
+    review::ReviewCounts,
+    task::TaskId,
+    workflow::{WorkItem, WorkItemKind},
+};
+
+/// End of run report, printed when a simulation stops.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub seed: u64,
+    pub clock: SimClock,
+    pub completed_tasks: Vec<(TaskId, usize)>,
+    pub delivered: Vec<Delivery>,
+    pub defects: DefectCounts,
+    pub reviews: ReviewCounts,
+    pub open_tasks: usize,
+    /// Work items still open past their due date when the run ended, by kind
+    pub overdue: Vec<(WorkItemKind, usize)>,
+    pub employees: Vec<EmployeeSummary>,
+}
+
+/// Work items of one kind that made it through their whole workflow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Delivery {
+    pub kind: WorkItemKind,
+    pub count: usize,
+    /// From showing up on the board to the last stage being done, in simulated hours
+    pub average_lead_time_hours: f64,
+    pub longest_lead_time_hours: f64,
+    /// Times their changes went through review, once for the ones approved right away
+    pub average_review_rounds: f64,
+    /// Delivered by their due date, the ones without a due date are neither on time nor late
+    pub on_time: usize,
+    pub late: usize,
+    /// How far off the team's estimates were on average, as a share of the true size
+    pub average_estimate_error: f64,
+    /// Turned out bigger than estimated
+    pub underestimated: usize,
+    /// Times work on them went past the estimate
+    pub surprises: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmployeeSummary {
+    pub id: EmployeeId,
+    pub name: String,
+    pub characteristics: EmployeeCharacteristics,
+    pub resources: EmployeeResources,
+    /// Work items they wrote changes for
+    pub authored: usize,
+    /// Work items they reviewed
+    pub reviewed: usize,
+    /// Times stress sent them on sick leave
+    pub burnouts: u32,
+    /// Their characteristics at the start of every day
+    pub history: Vec<SkillSample>,
+    /// Day they left the team on, `None` when they stayed until the end
+    pub left_on_day: Option<u64>,
+}
+
+impl Summary {
+    pub fn new(kanban: &Kanban) -> Self {
+        Summary {
+            seed: kanban.seed,
+            clock: kanban.clock,
+            completed_tasks: kanban
+                .completed_tasks
+                .iter()
+                .map(|(id, count)| (*id, *count))
+                .collect(),
+            delivered: Summary::deliveries(kanban),
+            defects: kanban.defects,
+            reviews: kanban.reviews,
+            open_tasks: kanban.task_list.len(),
+            overdue: Summary::overdue(kanban),
+            employees: kanban
+                .employee_data
+                .values()
+                .map(|employee| (employee, None))
+                .chain(
+                    kanban
+                        .former_employees
+                        .values()
+                        .map(|(day, employee)| (employee, Some(*day))),
+                )
+                .map(|(employee, left_on_day)| {
+                    let id = employee.employee_id;
+                    let work_items = kanban.delivered.iter().chain(kanban.work_items.values());
+                    let (authored, reviewed) =
+                        work_items.fold((0, 0), |(authored, reviewed), work_item| {
+                            (
+                                authored + work_item.authors().contains(&id) as usize,
+                                reviewed + work_item.reviewers().contains(&id) as usize,
+                            )
+                        });
+
+                    EmployeeSummary {
+                        id,
+                        name: employee.employee_name.clone(),
+                        characteristics: employee.characteristics,
+                        resources: employee.resources,
+                        authored,
+                        reviewed,
+                        burnouts: employee.burnouts,
+                        history: kanban.skill_history.get(&id).cloned().unwrap_or_default(),
+                        left_on_day,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn overdue(kanban: &Kanban) -> Vec<(WorkItemKind, usize)> {
+        let mut overdue: BTreeMap<WorkItemKind, usize> = BTreeMap::new();
+        for work_item in kanban.work_items.values() {
+            if work_item.is_overdue(kanban.clock.ticks()) {
+                *overdue.entry(work_item.kind).or_default() += 1;
+            }
+        }
+
+        overdue.into_iter().collect()
+    }
+
+    fn deliveries(kanban: &Kanban) -> Vec<Delivery> {
+        let minutes_per_tick = kanban.clock.minutes_per_tick() as f64;
+        let mut delivered: BTreeMap<WorkItemKind, Vec<&WorkItem>> = BTreeMap::new();
+        for work_item in kanban.delivered.iter() {
+            delivered.entry(work_item.kind).or_default().push(work_item);
+        }
+
+        delivered
+            .into_iter()
+            .map(|(kind, work_items)| {
+                let count = work_items.len();
+                let hours = work_items.iter().map(|work_item| {
+                    work_item.lead_time().unwrap_or_default() as f64 * minutes_per_tick / 60.
+                });
+                let estimates = work_items
+                    .iter()
+                    .filter_map(|work_item| Some((work_item.estimate?, work_item.size)))
+                    .collect::<Vec<_>>();
+                let count_due = |on_time: bool| {
+                    work_items
+                        .iter()
+                        .filter(|work_item| work_item.due_at.is_some())
+                        .filter(|work_item| work_item.is_overdue(kanban.clock.ticks()) != on_time)
+                        .count()
+                };
+                Delivery {
+                    kind,
+                    count,
+                    average_lead_time_hours: hours.clone().sum::<f64>() / count as f64,
+                    longest_lead_time_hours: hours.fold(0., f64::max),
+                    average_review_rounds: work_items
+                        .iter()
+                        .map(|work_item| work_item.review_rounds as f64)
+                        .sum::<f64>()
+                        / count as f64,
+                    on_time: count_due(true),
+                    late: count_due(false),
+                    average_estimate_error: estimates
+                        .iter()
+                        .map(|(estimate, size)| ((estimate - size) / size).abs() as f64)
+                        .sum::<f64>()
+                        / estimates.len().max(1) as f64,
+                    underestimated: estimates
+                        .iter()
+                        .filter(|(estimate, size)| estimate < size)
+                        .count(),
+                    surprises: work_items.iter().map(|work_item| work_item.surprises).sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// Skill history of every employee as CSV, one row per employee and day
+    pub fn history_csv(&self) -> String {
+        let mut csv =
+            String::from("id,name,day,company_experience,programming_skills,rigor,fitness\n");
+        for employee in self.employees.iter() {
+            for sample in employee.history.iter() {
+                let characteristics = sample.characteristics;
+                csv.push_str(&format!(
+                    "{},{},{},{:.2},{:.2},{:.2},{:.2}\n",
+                    employee.id.0,
+                    employee.name,
+                    sample.day,
+                    characteristics.company_experience,
+                    characteristics.programming_skills,
+                    characteristics.rigor,
+                    characteristics.fitness
+                ));
+            }
+        }
+
+        csv
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Seed: {}", self.seed)?;
+        writeln!(
+            f,
+            "Simulated: {} ticks, until {}",
+            self.clock.ticks(),
+            self.clock
+        )?;
+
+        writeln!(f, "Completed tasks:")?;
+        for (id, count) in self.completed_tasks.iter() {
+            writeln!(f, "  {:<12} {}", format!("{:?}", id), count)?;
+        }
+        writeln!(f, "Delivered work items:")?;
+        for delivery in self.delivered.iter() {
+            write!(
+                f,
+                "  {:<12} {:<4} lead time {:.1}h on average, {:.1}h at most, {:.1} review rounds",
+                delivery.kind.to_string(),
+                delivery.count,
+                delivery.average_lead_time_hours,
+                delivery.longest_lead_time_hours,
+                delivery.average_review_rounds
+            )?;
+            if delivery.on_time + delivery.late > 0 {
+                write!(f, ", {} on time, {} late", delivery.on_time, delivery.late)?;
+            }
+            writeln!(f)?;
+        }
+        if !self.delivered.is_empty() {
+            writeln!(f, "Estimates:")?;
+        }
+        for delivery in self.delivered.iter() {
+            writeln!(
+                f,
+                "  {:<12} {:.0}% off on average, {} of {} too low, went past them {} times",
+                delivery.kind.to_string(),
+                delivery.average_estimate_error * 100.,
+                delivery.underestimated,
+                delivery.count,
+                delivery.surprises
+            )?;
+        }
+        writeln!(
+            f,
+            "Defects: {} introduced, {} caught before delivery, {} escaped, {} reported as bugs",
+            self.defects.introduced,
+            self.defects.caught,
+            self.defects.escaped,
+            self.defects.surfaced
+        )?;
+        writeln!(
+            f,
+            "Reviews: {} approved, {} sent back for changes, {} rejected",
+            self.reviews.approved, self.reviews.changes_requested, self.reviews.rejected
+        )?;
+        writeln!(f, "Open tasks: {}", self.open_tasks)?;
+        if !self.overdue.is_empty() {
+            writeln!(f, "Still open past their due date:")?;
+        }
+        for (kind, count) in self.overdue.iter() {
+            writeln!(f, "  {:<12} {}", kind.to_string(), count)?;
+        }
+
+        writeln!(f, "Employees:")?;
+        for employee in self.employees.iter() {
+            let characteristics = employee.characteristics;
+            let resources = employee.resources;
+            writeln!(
+                f,
+                "  {:<10} rigor {:.0}, experience {:.0}, skills {:.0}, fitness {:.0} | energy \
+                 {:.1}, focus {:.1}, stress {:.1} | authored {}, reviewed {}, burnouts {}",
+                employee.name,
+                characteristics.rigor,
+                characteristics.company_experience,
+                characteristics.programming_skills,
+                characteristics.fitness,
+                resources.energy,
+                resources.focus,
+                resources.stress,
+                employee.authored,
+                employee.reviewed,
+                employee.burnouts
+            )?;
+            if let Some(day) = employee.left_on_day {
+                writeln!(f, "  {:<10} left on day {}", "", day)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything needed to run a simulation again, and how it ended the first time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub seed: u64,
+    /// Where the scenario was loaded from, avatars are found relative to it
+    pub scenario_path: Option<PathBuf>,
+    /// Content of the scenario file at the time of the run, `None` for the built-in scenario
+    pub scenario: Option<String>,
+    pub summary: Summary,
+}
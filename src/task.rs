@@ -1,89 +1,47 @@
 use std::fmt::{self, Display};
 
-use actix::{Addr, Message};
+use actix::Message;
+use serde::{Deserialize, Serialize};
 
-use crate::employee::EmployeeActor;
+use crate::{
+    clock::SimClock,
+    employee::{EmployeeActor, EmployeeCharacteristics, EmployeeId, EmployeeResources},
+    stress::StressEvent,
+    workflow::WorkItemKind,
+};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum TaskId {
+    Design,
     CreatePR,
     ReviewPR,
+    Test,
     MergePR,
+    Deploy,
     CoffeeBreak,
 }
 
 impl TaskId {
-    pub fn priority(&self) -> u32 {
-        match *self {
-            TaskId::CreatePR => 0,
-            TaskId::ReviewPR => 1,
-            TaskId::MergePR => 2,
-            TaskId::CoffeeBreak => 3,
-        }
-    }
+    pub const ALL: [TaskId; 7] = [
+        TaskId::Design,
+        TaskId::CreatePR,
+        TaskId::ReviewPR,
+        TaskId::Test,
+        TaskId::MergePR,
+        TaskId::Deploy,
+        TaskId::CoffeeBreak,
+    ];
 
-    pub fn to_task(&self) -> Task {
+    /// Tasks further down the pipeline go first, to finish what has been started
+    pub fn priority(&self) -> u32 {
         match *self {
-            TaskId::CreatePR => Task {
-                id: TaskId::CreatePR,
-                total_energy_required: 10.0,
-                energy_taken_per_tick: 0.1,
-                energy_multipliers: TaskEnergyMultipliers {
-                    company_experience: 2.0,
-                    rigor: 2.0,
-                    programming_skills: 2.0,
-                    fitness: 1.0,
-                    energy: 1.2,
-                    focus: 1.5,
-                    stress: 0.5,
-                },
-                ..Task::default()
-            },
-            TaskId::ReviewPR => Task {
-                id: TaskId::ReviewPR,
-                total_energy_required: 10.0,
-                energy_taken_per_tick: 0.1,
-                energy_multipliers: TaskEnergyMultipliers {
-                    company_experience: 2.0,
-                    rigor: 2.0,
-                    programming_skills: 2.0,
-                    fitness: 1.0,
-                    energy: 1.2,
-                    focus: 1.5,
-                    stress: 0.5,
-                },
-                ..Task::default()
-            },
-            TaskId::MergePR => Task {
-                id: TaskId::MergePR,
-                total_energy_required: 1.0,
-                energy_taken_per_tick: 0.1,
-                energy_multipliers: TaskEnergyMultipliers {
-                    company_experience: 2.0,
-                    rigor: 2.0,
-                    programming_skills: 2.0,
-                    fitness: 1.0,
-                    energy: 1.2,
-                    focus: 1.5,
-                    stress: 0.5,
-                },
-                ..Task::default()
-            },
-            TaskId::CoffeeBreak => Task {
-                id: TaskId::CoffeeBreak,
-                total_energy_required: 1.0,
-                energy_taken_per_tick: 0.1,
-                energy_multipliers: TaskEnergyMultipliers {
-                    company_experience: 2.0,
-                    rigor: 2.0,
-                    programming_skills: 2.0,
-                    fitness: 1.0,
-                    energy: 1.2,
-                    focus: 1.5,
-                    stress: 0.5,
-                },
-                ..Task::default()
-            },
+            TaskId::Design => 0,
+            TaskId::CreatePR => 1,
+            TaskId::ReviewPR => 2,
+            TaskId::Test => 3,
+            TaskId::MergePR => 4,
+            TaskId::Deploy => 5,
+            TaskId::CoffeeBreak => 6,
         }
     }
 }
@@ -91,17 +49,27 @@ impl TaskId {
 impl Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let task_prefix = match self.id {
+            TaskId::Design => "[design] ",
             TaskId::CreatePR => "[create] ",
-            TaskId::MergePR => "[merge] ",
             TaskId::ReviewPR => "[review] ",
-            TaskId::CoffeeBreak => "",
+            TaskId::Test => "[test] ",
+            TaskId::MergePR => "[merge] ",
+            TaskId::Deploy => "[deploy] ",
+            TaskId::CoffeeBreak => return write!(f, "Coffee break"),
         };
 
-        write!(f, "{}{}", task_prefix, self.name)
+        write!(f, "{}{}", task_prefix, self.kind)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// How much each characteristic and resource of an employee counts when working on a task.
+///
+/// Characteristics, energy and focus all live on a 0 to 100 scale. Their weighted average, scaled to
+/// 0..1, is how well suited the employee is to the task: a task with a high `rigor` weight goes fast
+/// for rigorous employees whatever their programming skills. Stress is not part of the average, its
+/// weight is the share of the work lost by a fully stressed employee instead.
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
 pub struct TaskEnergyMultipliers {
     // Characteristics
     pub company_experience: f32,
@@ -115,16 +83,37 @@ pub struct TaskEnergyMultipliers {
 }
 
 impl TaskEnergyMultipliers {
-    pub fn get_energy_cost(&self, employee: &EmployeeActor) -> f32 {
-        (employee.characteristics.rigor
-            + employee.characteristics.fitness
-            + employee.characteristics.programming_skills
-            + employee.characteristics.company_experience
-            + employee.resources.focus * 2.0
-            + employee.resources.energy * 1.5
-            - employee.resources.stress * 3.)
-            * 1.
-            / 400.
+    /// Work output of a perfectly suited, unstressed employee, as a multiple of
+    /// `Task::energy_taken_per_tick`.
+    pub const MAX_OUTPUT: f32 = 2.0;
+
+    /// Multiplier applied to `Task::energy_taken_per_tick` for an employee in the given state.
+    pub fn get_energy_cost(
+        &self,
+        characteristics: &EmployeeCharacteristics,
+        resources: &EmployeeResources,
+    ) -> f32 {
+        let weighted = [
+            (self.company_experience, characteristics.company_experience),
+            (self.rigor, characteristics.rigor),
+            (self.programming_skills, characteristics.programming_skills),
+            (self.fitness, characteristics.fitness),
+            (self.energy, resources.energy),
+            (self.focus, resources.focus),
+        ];
+        let total_weight: f32 = weighted.iter().map(|(weight, _)| weight).sum();
+        let suitability = if total_weight > 0. {
+            weighted
+                .iter()
+                .map(|(weight, value)| weight * value.max(0.))
+                .sum::<f32>()
+                / (total_weight * 100.)
+        } else {
+            1.
+        };
+        let stress_penalty = (self.stress * resources.stress / 100.).clamp(0., 1.);
+
+        Self::MAX_OUTPUT * suitability * (1. - stress_penalty)
     }
 }
 
@@ -144,35 +133,49 @@ impl Default for TaskEnergyMultipliers {
 
 #[derive(Debug, Copy, Clone)]
 pub struct Task {
-    pub name: &'static str,
+    pub kind: WorkItemKind,
     pub id: TaskId,
+    /// Index of the task in the workflow of its kind
+    pub stage: usize,
     /// How much energy is needed in total
     pub total_energy_required: f32,
+    /// How much energy the team expects it to need, the true amount is only known once it is done
+    pub estimated_energy: f32,
     /// How much have been given already
     pub energy_taken: f32,
     /// How much energy will be given by actor at each tick
     pub energy_taken_per_tick: f32,
     pub energy_multipliers: TaskEnergyMultipliers,
+    /// Priority given by triage on top of the one of the stage, kept through the whole workflow
+    pub priority: u32,
+    /// Held up by something outside the team, nobody can work on it until it is cleared
+    pub impeded: bool,
+    /// Goes over a change again after review, reserved to its authors while any of them is around
+    pub rework: bool,
 }
 
 impl Task {
-    #[allow(unused)]
     pub fn is_done(&self) -> bool {
         self.total_energy_required <= self.energy_taken
     }
 
-    pub fn progress(&self) -> f32 {
-        self.energy_taken / self.total_energy_required
+    /// Progress as the team sees it, against their estimate
+    pub fn estimated_progress(&self) -> f32 {
+        if self.is_done() {
+            1.
+        } else {
+            (self.energy_taken / self.estimated_energy).min(1.)
+        }
     }
 
-    pub fn as_feature(&mut self) -> Self {
-        self.name = "Feature";
-
-        *self
+    /// Energy the team expects it still needs
+    pub fn estimated_energy_left(&self) -> f32 {
+        (self.estimated_energy - self.energy_taken).max(0.)
     }
 
-    pub fn as_bug_fix(&mut self) -> Self {
-        self.name = "Bug fix";
+    pub fn as_stage(&mut self, kind: WorkItemKind, stage: usize) -> Self {
+        self.kind = kind;
+        self.stage = stage;
 
         *self
     }
@@ -181,12 +184,17 @@ impl Task {
 impl Default for Task {
     fn default() -> Self {
         Task {
-            name: "Feature",
+            kind: WorkItemKind::Feature,
             id: TaskId::CreatePR,
+            stage: 0,
             total_energy_required: 5.0,
+            estimated_energy: 5.0,
             energy_taken: 0.0,
             energy_taken_per_tick: 0.1,
             energy_multipliers: TaskEnergyMultipliers::default(),
+            priority: 0,
+            impeded: false,
+            rework: false,
         }
     }
 }
@@ -196,21 +204,112 @@ impl Message for Task {
 }
 
 pub struct Work {
-    pub task: TaskId,
+    pub task: Task,
     pub uuid: usize,
+    /// Done after working hours, at a higher energy cost
+    pub overtime: bool,
+    /// What the work puts the employee through on top of the work itself
+    pub stress_events: Vec<StressEvent>,
+    pub clock: SimClock,
+    /// Characteristics of the most skilled colleague who worked on the task too
+    pub pair: Option<EmployeeCharacteristics>,
 }
 
 impl Message for Work {
-    type Result = ();
+    type Result = WorkCompleted;
 }
 
 pub struct WorkCompleted {
-    pub employee_address: Addr<EmployeeActor>,
-    pub employee_name: &'static str,
+    pub employee_id: EmployeeId,
     pub uuid: usize,
     pub energy_add: f32,
+    /// Tasks the employee decided to put on the board while working
+    pub spawned_tasks: Vec<TaskId>,
+    /// Snapshot of the employee after the work was done
+    pub employee_data: EmployeeActor,
 }
 
-impl Message for WorkCompleted {
-    type Result = ();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+
+    fn characteristics(rigor: f32, programming_skills: f32) -> EmployeeCharacteristics {
+        EmployeeCharacteristics {
+            company_experience: 50.,
+            rigor,
+            programming_skills,
+            fitness: 50.,
+        }
+    }
+
+    fn cost(id: TaskId, characteristics: &EmployeeCharacteristics, stress: f32) -> f32 {
+        let resources = EmployeeResources {
+            stress,
+            ..EmployeeResources::new()
+        };
+        Scenario::builtin()
+            .task(id)
+            .energy_multipliers
+            .get_energy_cost(characteristics, &resources)
+    }
+
+    #[test]
+    fn review_favours_rigor() {
+        let rigorous = characteristics(90., 20.);
+        let hacker = characteristics(20., 90.);
+
+        assert!(cost(TaskId::ReviewPR, &rigorous, 0.) > cost(TaskId::ReviewPR, &hacker, 0.));
+    }
+
+    #[test]
+    fn create_pr_favours_programming_skills() {
+        let rigorous = characteristics(90., 20.);
+        let hacker = characteristics(20., 90.);
+
+        assert!(cost(TaskId::CreatePR, &hacker, 0.) > cost(TaskId::CreatePR, &rigorous, 0.));
+    }
+
+    #[test]
+    fn stress_slows_work_down() {
+        let employee = characteristics(50., 50.);
+
+        assert!(cost(TaskId::CreatePR, &employee, 60.) < cost(TaskId::CreatePR, &employee, 0.));
+    }
+
+    #[test]
+    fn weights_scale_the_output() {
+        let multipliers = TaskEnergyMultipliers {
+            company_experience: 0.,
+            rigor: 1.,
+            programming_skills: 0.,
+            fitness: 0.,
+            energy: 0.,
+            focus: 0.,
+            stress: 0.,
+        };
+        let resources = EmployeeResources::new();
+
+        let cost = multipliers.get_energy_cost(&characteristics(25., 100.), &resources);
+
+        assert!((cost - TaskEnergyMultipliers::MAX_OUTPUT * 0.25).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn no_weights_means_full_output() {
+        let multipliers = TaskEnergyMultipliers {
+            company_experience: 0.,
+            rigor: 0.,
+            programming_skills: 0.,
+            fitness: 0.,
+            energy: 0.,
+            focus: 0.,
+            stress: 0.,
+        };
+        let resources = EmployeeResources::new();
+
+        let cost = multipliers.get_energy_cost(&characteristics(50., 50.), &resources);
+
+        assert_eq!(cost, TaskEnergyMultipliers::MAX_OUTPUT);
+    }
 }
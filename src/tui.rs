@@ -0,0 +1,628 @@
+use std::{
+    collections::BTreeSet,
+    io::{stdout, Stdout, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor, event,
+    event::{poll, Event, KeyCode, KeyEvent},
+    queue, style,
+    style::{Color, Stylize},
+    terminal,
+    terminal::{disable_raw_mode, enable_raw_mode},
+    ExecutableCommand,
+};
+
+use crate::{
+    clock::{DayPhase, Pace, SimClock},
+    column::Column,
+    dependency::Dependency,
+    employee::{EmployeeActor, EmployeeId},
+    kanban::Kanban,
+    task::{Task, TaskId},
+};
+
+/// What a key press asks the kanban to do
+pub enum KeyAction {
+    Quit,
+    /// Run a single tick while paused
+    Step,
+    TogglePause,
+    Faster,
+    Slower,
+    /// Only the view changed
+    Redraw,
+}
+
+/// Terminal front-end of the kanban. Owns the terminal for as long as it lives.
+pub struct Tui {
+    stdout: Stdout,
+    curr_employee: usize,
+}
+
+impl Tui {
+    pub fn new() -> Self {
+        enable_raw_mode().unwrap();
+
+        let mut stdout = stdout();
+        stdout
+            .execute(terminal::Clear(terminal::ClearType::All))
+            .unwrap();
+
+        // Reset terminal
+        queue!(
+            stdout,
+            terminal::EnterAlternateScreen,
+            style::ResetColor,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(1, 1),
+            cursor::Show,
+            cursor::EnableBlinking
+        )
+        .unwrap();
+
+        stdout.flush().unwrap();
+        Tui {
+            stdout,
+            curr_employee: 0,
+        }
+    }
+
+    /// Reads a pending key press, if any, without blocking.
+    pub fn handle_keys(&mut self, employee_count: usize) -> Option<KeyAction> {
+        if !poll(Duration::ZERO).unwrap() {
+            return None;
+        }
+
+        match event::read() {
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Left,
+                ..
+            })) => {
+                if self.curr_employee == 0 {
+                    self.curr_employee = employee_count.saturating_sub(1);
+                } else {
+                    self.curr_employee -= 1;
+                }
+                Some(KeyAction::Redraw)
+            }
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Right,
+                ..
+            })) => {
+                self.curr_employee = (self.curr_employee + 1) % employee_count.max(1);
+                Some(KeyAction::Redraw)
+            }
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Esc,
+                ..
+            })) => Some(KeyAction::Quit),
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Char(' '),
+                ..
+            })) => Some(KeyAction::Step),
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Char('p'),
+                ..
+            })) => Some(KeyAction::TogglePause),
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Char('+'),
+                ..
+            })) => Some(KeyAction::Faster),
+            Ok(Event::Key(KeyEvent {
+                code: KeyCode::Char('-'),
+                ..
+            })) => Some(KeyAction::Slower),
+            _ => None,
+        }
+    }
+
+    pub fn draw(&mut self, kanban: &Kanban) {
+        let sorted_task_list = kanban.sorted_task_list();
+        let max_bar_width = 15;
+        let progress_color = Color::Green;
+        let done_color = Color::Blue;
+        let waiting_color = Color::Yellow;
+        let impeded_color = Color::Red;
+        let blocked_color = Color::Magenta;
+        queue!(
+            self.stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+        )
+        .unwrap();
+        let employees = kanban
+            .employee_data
+            .keys()
+            .copied()
+            .collect::<Vec<EmployeeId>>();
+        // The team can shrink under the selection
+        self.curr_employee = self.curr_employee.min(employees.len().saturating_sub(1));
+        if let Some(employee_id) = employees.get(self.curr_employee) {
+            let curr_employee = kanban.employee_data.get(employee_id).unwrap();
+            let mut employee_tasks = vec![];
+            for (_id, (task, contributors)) in kanban.task_list.iter() {
+                if contributors.contains(employee_id) {
+                    employee_tasks.push(*task);
+                }
+            }
+            draw_employee_card(&mut self.stdout, curr_employee, &employee_tasks, kanban);
+        }
+
+        // List all employees
+        let employess = BTreeSet::from_iter(employees);
+        queue!(self.stdout, style::Print("Super Dev Organization: ".red()),).unwrap();
+        draw_contributors(&mut self.stdout, kanban, &employess);
+        queue!(self.stdout, cursor::MoveToNextLine(1),).unwrap();
+
+        draw_time_bar(
+            &mut self.stdout,
+            &kanban.clock,
+            kanban.day_phase,
+            kanban.pace,
+            kanban.seed,
+        );
+
+        draw_columns(&mut self.stdout, kanban);
+
+        let capped_list = &sorted_task_list[0..6.min(sorted_task_list.len())];
+        for (uuid, (task, contributors)) in capped_list.iter() {
+            // Start row
+            queue!(self.stdout, cursor::MoveToNextLine(1)).unwrap();
+            let title = task_title(
+                '䷢',
+                *uuid,
+                task,
+                kanban.story_points(*uuid),
+                kanban.fixes(*uuid),
+            );
+
+            // Title, overdue work items in red and the ones getting close to their due date in
+            // yellow
+            let title = if kanban.is_overdue(*uuid) {
+                title.red().bold()
+            } else if kanban.escalation(*uuid) > 0 {
+                title.yellow()
+            } else {
+                title.white()
+            };
+            queue!(self.stdout, style::PrintStyledContent(title)).unwrap();
+
+            // Progress bar + percentage against the estimate, finished stages wait for room in the
+            // next column, impeded tasks for a manager and blocked ones for their dependencies
+            let color = if task.impeded {
+                impeded_color
+            } else if kanban.is_blocked(*uuid) {
+                blocked_color
+            } else if task.is_done() {
+                waiting_color
+            } else {
+                progress_color
+            };
+            draw_task_progress(
+                &mut self.stdout,
+                color,
+                task.estimated_progress(),
+                max_bar_width,
+            );
+
+            draw_contributors(&mut self.stdout, kanban, contributors);
+        }
+
+        // Draw blocked work items with what they wait on
+        let blocked = sorted_task_list
+            .iter()
+            .filter(|(uuid, _)| kanban.is_blocked(*uuid))
+            .take(4)
+            .collect::<Vec<_>>();
+        if !blocked.is_empty() {
+            queue!(
+                self.stdout,
+                cursor::MoveToNextLine(1),
+                cursor::MoveToNextLine(1),
+                style::Print("Blocked"),
+                cursor::MoveToNextLine(1)
+            )
+            .unwrap();
+        }
+        for (uuid, (task, _)) in blocked {
+            queue!(
+                self.stdout,
+                cursor::MoveToNextLine(1),
+                style::Print(task_title(
+                    '⧗',
+                    *uuid,
+                    task,
+                    kanban.story_points(*uuid),
+                    kanban.fixes(*uuid)
+                )),
+                style::PrintStyledContent(blocking_chain(kanban, *uuid).with(blocked_color)),
+            )
+            .unwrap();
+        }
+
+        if !kanban.done_list.is_empty() {
+            queue!(
+                self.stdout,
+                cursor::MoveToNextLine(1),
+                cursor::MoveToNextLine(1),
+                style::Print("Done"),
+                cursor::MoveToNextLine(1)
+            )
+            .unwrap();
+        }
+
+        // Draw done tasks
+        for (uuid, task, contributors) in kanban.done_list.iter() {
+            let print = style::Print(task_title('✓', *uuid, task, None, kanban.fixes(*uuid)));
+
+            queue!(self.stdout, cursor::MoveToNextLine(1), print).unwrap();
+            draw_task_progress(&mut self.stdout, done_color, 1.0, max_bar_width);
+
+            draw_contributors(&mut self.stdout, kanban, contributors);
+        }
+
+        // Flush last
+        self.stdout.flush().unwrap();
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        queue!(
+            self.stdout,
+            terminal::LeaveAlternateScreen,
+            style::ResetColor,
+            cursor::Show
+        )
+        .unwrap();
+        self.stdout.flush().unwrap();
+        disable_raw_mode().unwrap();
+    }
+}
+
+/// Stage and work item of a task, the uuid stays the same through the whole workflow, with the
+/// story points the team gave the work item and the one a bug fix is for
+fn task_title(
+    marker: char,
+    uuid: usize,
+    task: &Task,
+    points: Option<u32>,
+    fixes: Option<usize>,
+) -> String {
+    let stage = if task.rework {
+        "Rework".to_string()
+    } else {
+        format!("{:?}", task.id)
+    };
+    let title = if task.id == TaskId::CoffeeBreak {
+        format!("{} [{:<11}]", marker, stage)
+    } else {
+        let points = points.map_or(String::new(), |points| format!(" {}pt", points));
+        let fixes = fixes.map_or(String::new(), |origin| format!(" fixes #{}", origin));
+        format!(
+            "{} [{:<11}] {} #{}{}{}",
+            marker, stage, task.kind, uuid, points, fixes
+        )
+    };
+
+    format!("{0: <43}", title)
+}
+
+/// Everything a blocked work item waits on, one after the other
+fn blocking_chain(kanban: &Kanban, uuid: usize) -> String {
+    let chain = kanban
+        .blocking_chain(uuid)
+        .into_iter()
+        .map(|dependency| match dependency {
+            Dependency::WorkItem(prerequisite) => format!("#{}", prerequisite),
+            Dependency::OtherTeam {
+                ready_at,
+            } => format!(
+                "another team (day {})",
+                ready_at / kanban.clock.ticks_per_day()
+            ),
+        })
+        .collect::<Vec<_>>();
+
+    format!("waiting on {}", chain.join(" → "))
+}
+
+fn draw_task_progress<W>(w: &mut W, color: Color, progress: f32, max_width: u16)
+where
+    W: Write,
+{
+    let limit = (progress * max_width as f32).round() as u16;
+    for col in 0..=max_width {
+        if col < limit || progress == 1.0 {
+            queue!(w, style::SetForegroundColor(color), style::Print("███")).unwrap();
+        } else {
+            queue!(
+                w,
+                style::SetForegroundColor(Color::Black),
+                style::Print("███")
+            )
+            .unwrap();
+        }
+    }
+    queue!(
+        w,
+        style::SetForegroundColor(Color::White),
+        style::Print(format!(" {:.2} % ", progress * 100.0)),
+    )
+    .unwrap();
+}
+
+fn draw_contributors<W>(w: &mut W, kanban: &Kanban, contributors: &BTreeSet<EmployeeId>)
+where
+    W: Write,
+{
+    let mut count = 0;
+    for c in contributors.iter() {
+        queue!(w, style::Print(kanban.employee_name(*c))).unwrap();
+        count += 1;
+        if count < contributors.len() {
+            queue!(w, style::Print(", ")).unwrap();
+        }
+    }
+}
+
+fn draw_employee_card<W>(
+    w: &mut W,
+    employee: &EmployeeActor,
+    employee_tasks: &[Task],
+    kanban: &Kanban,
+) where
+    W: Write,
+{
+    let card_height = 20;
+    let card_width = 90;
+
+    for y in 0..card_height {
+        for x in 0..card_width {
+            if (y == 0 || y == card_height - 1) || (x == 0 || x == card_width - 1) {
+                queue!(
+                    w,
+                    cursor::MoveTo(x, y),
+                    style::PrintStyledContent("█".underlined().green())
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let employee_id = &employee.employee_id;
+    if let Some(avatar) = kanban.avatars.get(employee_id) {
+        queue!(w, cursor::MoveTo(1, 1),).unwrap();
+        draw_avatar(w, avatar);
+    }
+
+    if employee.sick_days > 0 {
+        queue!(
+            w,
+            cursor::MoveTo(5, 10),
+            style::PrintStyledContent(
+                format!("Burnt out, on sick leave for {} days", employee.sick_days)
+                    .underlined()
+                    .red()
+            ),
+        )
+        .unwrap();
+    } else {
+        draw_current_tasks(w, employee_tasks);
+    }
+    if employee.is_onboarding() {
+        let onboarding = match kanban.mentors.get(employee_id) {
+            Some(mentor) => format!(
+                "Onboarding for {} more days, mentored by {}",
+                employee.onboarding_days,
+                kanban.employee_name(*mentor)
+            ),
+            None => format!("Onboarding for {} more days", employee.onboarding_days),
+        };
+        queue!(
+            w,
+            cursor::MoveTo(5, 18),
+            style::PrintStyledContent(onboarding.yellow()),
+        )
+        .unwrap();
+    }
+
+    draw_characteristics(w, employee);
+
+    draw_resources(w, employee);
+
+    draw_effects(w, employee, &kanban.clock);
+
+    queue!(w, cursor::MoveTo(0, card_height + 1),).unwrap();
+}
+
+fn draw_characteristics<W>(w: &mut W, employee: &EmployeeActor)
+where
+    W: Write,
+{
+    let section_start = (69, 2);
+
+    queue!(
+        w,
+        cursor::MoveTo(section_start.0, section_start.1),
+        style::PrintStyledContent("Characteristics".underlined().red()),
+        cursor::MoveTo(section_start.0, section_start.1 + 2),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Rigor: {:.0}", employee.characteristics.rigor)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 3),
+        style::Print(&format!(
+            "{0: <20}",
+            format!(
+                "Experience: {:.0}",
+                employee.characteristics.company_experience
+            )
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 4),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Skills: {:.0}", employee.characteristics.programming_skills)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 5),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Fitness: {:.0}", employee.characteristics.fitness)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 6),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Role: {:?}", employee.employee_type)
+        )),
+    )
+    .unwrap();
+}
+
+fn draw_resources<W>(w: &mut W, employee: &EmployeeActor)
+where
+    W: Write,
+{
+    let section_start = (69, 9);
+
+    queue!(
+        w,
+        cursor::MoveTo(section_start.0, section_start.1),
+        style::PrintStyledContent("Resources".underlined().red()),
+        cursor::MoveTo(section_start.0, section_start.1 + 2),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Energy: {:.0}", employee.resources.energy)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 3),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Focus: {:.0}", employee.resources.focus)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 4),
+        style::Print(&format!(
+            "{0: <20}",
+            format!("Stress: {:.0}", employee.resources.stress)
+        )),
+        cursor::MoveTo(section_start.0, section_start.1 + 5),
+    )
+    .unwrap();
+}
+
+/// Active effects with the ticks they have left
+fn draw_effects<W>(w: &mut W, employee: &EmployeeActor, clock: &SimClock)
+where
+    W: Write,
+{
+    let section_start = (69, 15);
+
+    queue!(
+        w,
+        cursor::MoveTo(section_start.0, section_start.1),
+        style::PrintStyledContent("Effects".underlined().red()),
+    )
+    .unwrap();
+    let active = employee
+        .effects
+        .iter()
+        .filter(|effect| effect.ticks_left(clock) > 0)
+        .take(3);
+    for (i, effect) in active.enumerate() {
+        let stacks = if effect.stacks > 1 {
+            format!(" x{}", effect.stacks)
+        } else {
+            String::new()
+        };
+        queue!(
+            w,
+            cursor::MoveTo(section_start.0, section_start.1 + 1 + i as u16),
+            style::Print(&format!(
+                "{0: <20}",
+                format!("{}{} {}t", effect.id, stacks, effect.ticks_left(clock))
+            )),
+        )
+        .unwrap();
+    }
+}
+
+fn draw_current_tasks<W>(w: &mut W, employee_tasks: &[Task])
+where
+    W: Write,
+{
+    let section_start = (5, 10);
+
+    queue!(
+        w,
+        cursor::MoveTo(section_start.0, section_start.1),
+        style::PrintStyledContent("Ongoing tasks".underlined().red()),
+    )
+    .unwrap();
+    let capped_tasks = &employee_tasks[0..5.min(employee_tasks.len())];
+    for (i, task) in capped_tasks.iter().enumerate() {
+        queue!(
+            w,
+            cursor::MoveTo(section_start.0, section_start.1 + 2 + i as u16),
+            style::PrintStyledContent(format!("[{:?}] ", task.id).green()),
+            style::PrintStyledContent(format!("{0: <10}", task.kind.to_string()).white()),
+        )
+        .unwrap();
+        draw_task_progress(w, Color::Green, task.estimated_progress(), 10);
+    }
+}
+
+fn draw_avatar<W>(w: &mut W, avatar: &str)
+where
+    W: Write,
+{
+    let data = avatar.split('\n');
+
+    queue!(w, cursor::MoveRight(4), cursor::MoveDown(1)).unwrap();
+
+    for d in data {
+        queue!(
+            w,
+            style::PrintStyledContent(d.to_string().red()),
+            cursor::MoveToNextLine(1),
+            cursor::MoveRight(5)
+        )
+        .unwrap();
+    }
+}
+
+/// Work items in every column of the board, columns at their limit in red
+fn draw_columns<W>(w: &mut W, kanban: &Kanban)
+where
+    W: Write,
+{
+    for (index, column) in Column::ALL.into_iter().enumerate() {
+        if index > 0 {
+            queue!(w, style::Print(" | ")).unwrap();
+        }
+
+        let count = kanban.column_count(column, None);
+        let content = match kanban.wip_limits().limit(column) {
+            Some(limit) if count >= limit => format!("{} {}/{}", column, count, limit).red().bold(),
+            Some(limit) => format!("{} {}/{}", column, count, limit).white(),
+            None => format!("{} {}", column, count).white(),
+        };
+        queue!(w, style::PrintStyledContent(content)).unwrap();
+    }
+
+    queue!(w, cursor::MoveToNextLine(1)).unwrap();
+}
+
+fn draw_time_bar<W>(w: &mut W, clock: &SimClock, day_phase: DayPhase, pace: Pace, seed: u64)
+where
+    W: Write,
+{
+    queue!(
+        w,
+        cursor::MoveToNextLine(1),
+        style::PrintStyledContent(clock.to_string().underlined().green()),
+        style::Print(format!(" ({})  Speed: {}  Seed: {}", day_phase, pace, seed)),
+        cursor::MoveToNextLine(1),
+        cursor::MoveToNextLine(1),
+    )
+    .unwrap();
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{column::Column, dependency::Dependency, employee::EmployeeId, task::TaskId};
+
+/// What a piece of work on the board is about, each kind goes through its own workflow
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum WorkItemKind {
+    Feature,
+    BugFix,
+    Chore,
+}
+
+impl WorkItemKind {
+    pub const ALL: [WorkItemKind; 3] = [
+        WorkItemKind::Feature,
+        WorkItemKind::BugFix,
+        WorkItemKind::Chore,
+    ];
+}
+
+impl Display for WorkItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WorkItemKind::Feature => "Feature",
+            WorkItemKind::BugFix => "Bug fix",
+            WorkItemKind::Chore => "Chore",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Stages a work item goes through, per kind. Finishing the task of one stage puts the task of the
+/// next one on the board, until the last stage is done.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Workflows(BTreeMap<WorkItemKind, Vec<TaskId>>);
+
+impl Workflows {
+    pub fn stages(&self, kind: WorkItemKind) -> &[TaskId] {
+        self.0.get(&kind).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Stage that follows `stage` for `kind`, `None` once the work item is done.
+    pub fn next_stage(&self, kind: WorkItemKind, stage: usize) -> Option<TaskId> {
+        self.stages(kind).get(stage + 1).copied()
+    }
+
+    /// Takes the workflows of `fallback` for the kinds that don't have one.
+    pub fn merge(&mut self, fallback: Workflows) {
+        for (kind, stages) in fallback.0 {
+            self.0.entry(kind).or_insert(stages);
+        }
+    }
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for kind in WorkItemKind::ALL {
+            let stages = self.stages(kind);
+            if stages.is_empty() {
+                problems.push(format!("workflows.{:?} needs at least one stage", kind));
+            }
+            if stages.contains(&TaskId::CoffeeBreak) {
+                problems.push(format!(
+                    "workflows.{:?}: CoffeeBreak can't be a workflow stage",
+                    kind
+                ));
+            }
+            // Work items waiting on a column to their left could block each other for good
+            if stages
+                .windows(2)
+                .any(|pair| Column::of_stage(pair[1]) < Column::of_stage(pair[0]))
+            {
+                problems.push(format!(
+                    "workflows.{:?}: stages have to go through the columns from left to right",
+                    kind
+                ));
+            }
+        }
+    }
+}
+
+/// One feature, bug fix or chore followed through every stage of its workflow. Its tasks keep the
+/// same uuid on the board, whichever stage they are at.
+#[derive(Debug, Clone)]
+pub struct WorkItem {
+    pub kind: WorkItemKind,
+    /// Tick the work item showed up on the board
+    pub created_at: u64,
+    /// Tick its last stage was finished
+    pub delivered_at: Option<u64>,
+    /// Tick it has to be delivered by, if any
+    pub due_at: Option<u64>,
+    /// Who worked on every finished stage, in workflow order
+    pub stages: Vec<(TaskId, BTreeSet<EmployeeId>)>,
+    /// Defects in its changes nobody spotted yet, the ones still there on delivery escape
+    pub latent_defects: u32,
+    /// Uuid of the delivered work item whose defect this one fixes
+    pub fixes: Option<usize>,
+    /// Times its change went through review
+    pub review_rounds: u32,
+    /// What has to be done before anyone can start on it
+    pub dependencies: BTreeSet<Dependency>,
+    /// True size, as a multiple of a typical work item of its kind. Scales the energy every stage
+    /// needs, nobody on the team knows it
+    pub size: f32,
+    /// Size the team expects it to be, `None` until they estimated it
+    pub estimate: Option<f32>,
+    /// Times work on it went past the estimate and the team had to look at it again
+    pub surprises: u32,
+}
+
+impl WorkItem {
+    pub fn new(kind: WorkItemKind, created_at: u64) -> Self {
+        WorkItem {
+            kind,
+            created_at,
+            delivered_at: None,
+            due_at: None,
+            stages: vec![],
+            latent_defects: 0,
+            fixes: None,
+            review_rounds: 0,
+            dependencies: BTreeSet::new(),
+            size: 1.,
+            estimate: None,
+            surprises: 0,
+        }
+    }
+
+    /// Ticks between the work item showing up and its last stage being done
+    pub fn lead_time(&self) -> Option<u64> {
+        self.delivered_at
+            .map(|delivered_at| delivered_at - self.created_at)
+    }
+
+    /// Whether it went past its due date, by tick `now` or on delivery
+    pub fn is_overdue(&self, now: u64) -> bool {
+        let end = self.delivered_at.unwrap_or(now);
+        self.due_at.is_some_and(|due_at| end > due_at)
+    }
+
+    /// Everyone who wrote the changes
+    pub fn authors(&self) -> BTreeSet<EmployeeId> {
+        self.contributors_of(TaskId::CreatePR)
+    }
+
+    /// Everyone who reviewed the changes
+    pub fn reviewers(&self) -> BTreeSet<EmployeeId> {
+        self.contributors_of(TaskId::ReviewPR)
+    }
+
+    fn contributors_of(&self, id: TaskId) -> BTreeSet<EmployeeId> {
+        self.stages
+            .iter()
+            .filter(|(stage, _)| *stage == id)
+            .flat_map(|(_, contributors)| contributors.iter().copied())
+            .collect()
+    }
+}